@@ -0,0 +1,557 @@
+// Parsing of #[builder(...)] on the input type and on its fields.
+//
+// Every problem with the attributes is collected instead of stopping at the
+// first one, so that a single compile reports all of them, each spanned at the
+// key or value at fault. A misspelled key names the one that was meant:
+//
+//     #[builder(eac = "arg")]
+//       ^^^^^^^^^^^^^^^^^^^^ expected `builder(each = "...")`
+
+use proc_macro2::TokenTree;
+use quote::ToTokens;
+use syn::meta::ParseNestedMeta;
+
+use crate::{Merge, Pattern};
+
+// The keys accepted on the struct, and how each one is written.
+const CONTAINER_KEYS: &[(&str, &str)] = &[
+    ("typestate", "typestate"),
+    ("default", "default"),
+    ("setter", "setter(...)"),
+    ("validate", "validate = \"...\""),
+    ("pattern", "pattern = \"...\""),
+    ("serde", "serde"),
+    ("name", "name = \"...\""),
+    ("vis", "vis = \"...\""),
+    ("constructor", "constructor = \"...\""),
+    ("no_std", "no_std"),
+    ("no_from", "no_from"),
+    ("no_debug", "no_debug"),
+    ("build_error", "build_error = \"...\""),
+];
+
+// The keys accepted on a field, and how each one is written.
+const FIELD_KEYS: &[(&str, &str)] = &[
+    ("each", "each = \"...\""),
+    ("default", "default"),
+    ("default_async", "default_async = \"...\""),
+    ("setter", "setter(...)"),
+    ("skip", "skip"),
+    ("rename", "rename = \"...\""),
+    ("name", "name = \"...\""),
+    ("vis", "vis = \"...\""),
+    ("doc", "doc = \"...\""),
+    ("validate", "validate = \"...\""),
+    ("merge", "merge = \"...\""),
+    ("sub_builder", "sub_builder"),
+    ("secret", "secret"),
+    ("collection", "collection = \"...\""),
+];
+
+// The options inside setter(...), on the struct or on a field.
+const SETTER_KEYS: &[(&str, &str)] = &[("into", "into"), ("strip_option", "strip_option")];
+
+// Field keys that make no sense together. A skipped field has no setter to
+// configure, and a sub-builder field is set through its own builder.
+const FIELD_CONFLICTS: &[(&str, &str)] = &[
+    ("skip", "each"),
+    ("skip", "setter"),
+    ("skip", "rename"),
+    ("skip", "name"),
+    ("skip", "vis"),
+    ("skip", "doc"),
+    ("skip", "validate"),
+    ("skip", "merge"),
+    ("skip", "sub_builder"),
+    ("skip", "default_async"),
+    ("skip", "secret"),
+    ("skip", "collection"),
+    ("default_async", "default"),
+    ("default_async", "each"),
+    ("default_async", "sub_builder"),
+    ("sub_builder", "each"),
+    ("sub_builder", "default"),
+    ("sub_builder", "setter"),
+    ("name", "rename"),
+];
+
+// Errors collected while parsing, combined into one when they are returned.
+#[derive(Default)]
+pub(crate) struct Errors {
+    error: Option<syn::Error>,
+}
+
+impl Errors {
+    pub(crate) fn push(&mut self, error: syn::Error) {
+        match &mut self.error {
+            Some(errors) => errors.combine(error),
+            None => self.error = Some(error),
+        }
+    }
+
+    pub(crate) fn finish(self) -> Result<(), syn::Error> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+// Options given on the struct itself with #[builder(...)].
+pub(crate) struct ContainerAttrs {
+    // Track which required fields have been set in the builder's type, so that
+    // a missing field is a compile error instead of a runtime one.
+    pub(crate) typestate: bool,
+    // Take every unset field from the struct's own Default impl.
+    pub(crate) default: Option<syn::Path>,
+    // setter(into) for every field.
+    pub(crate) setter_into: bool,
+    // fn(&T) -> Result<(), String> that build() runs on the built value.
+    pub(crate) validate: Option<syn::Path>,
+    pub(crate) pattern: Pattern,
+    // Derive serde::Deserialize for the builder, to load it from config files.
+    pub(crate) serde: Option<syn::Path>,
+    // The builder type, instead of {Type}Builder.
+    pub(crate) name: Option<syn::Ident>,
+    // Visibility of the builder, its error type and all generated methods,
+    // `pub` unless given.
+    pub(crate) vis: syn::Visibility,
+    // The method that creates the builder, instead of builder().
+    pub(crate) constructor: Option<syn::Ident>,
+    // Take Vec, String and format! from alloc instead of std in the generated
+    // code, which names everything else through core anyway.
+    pub(crate) no_std: bool,
+    // Leave out From<Type> for the builder, which takes the value apart and so
+    // cannot be compiled for a type that implements Drop.
+    pub(crate) no_from: bool,
+    // Leave out the builder's Debug impl, so that one can be written by hand.
+    pub(crate) no_debug: bool,
+    // The error type returned by build(), converted from the builder's own
+    // error type with From.
+    pub(crate) build_error: Option<syn::Path>,
+}
+
+impl ContainerAttrs {
+    pub(crate) fn parse(attrs: &[syn::Attribute], errors: &mut Errors) -> Self {
+        let mut typestate = None;
+        let mut default = None;
+        let mut setter_into = false;
+        let mut validate = None;
+        let mut pattern = None;
+        let mut serde = None;
+        let mut name = None;
+        let mut vis = None;
+        let mut constructor = None;
+        let mut no_std = false;
+        let mut no_from = false;
+        let mut no_debug = None;
+        let mut build_error = None;
+
+        parse_keys(attrs, CONTAINER_KEYS, errors, |key, meta, errors| {
+            match key {
+                "typestate" => typestate = Some(flag(meta)?),
+                "default" => default = Some(flag(meta)?),
+                // strip_option is what Option<T> fields do anyway, so on the
+                // struct it only needs to be accepted.
+                "setter" => {
+                    for option in setter_options(meta, errors)? {
+                        setter_into |= option.is_ident("into");
+                    }
+                }
+                "validate" => validate = Some(string(meta)?.parse()?),
+                "pattern" => {
+                    let lit = string(meta)?;
+                    pattern = Some(match lit.value().as_str() {
+                        "mutable" => (Pattern::Mutable, lit),
+                        "owned" => (Pattern::Owned, lit),
+                        "immutable" => (Pattern::Immutable, lit),
+                        _ => {
+                            return Err(syn::Error::new_spanned(
+                                lit,
+                                "expected `\"owned\"`, `\"mutable\"` or `\"immutable\"`",
+                            ));
+                        }
+                    });
+                }
+                "serde" => serde = Some(flag(meta)?),
+                "name" => name = Some(string(meta)?.parse()?),
+                "vis" => vis = Some(string(meta)?.parse()?),
+                "constructor" => constructor = Some(string(meta)?.parse()?),
+                "no_std" => {
+                    flag(meta)?;
+                    no_std = true;
+                }
+                "no_from" => {
+                    flag(meta)?;
+                    no_from = true;
+                }
+                "no_debug" => no_debug = Some(flag(meta)?),
+                "build_error" => build_error = Some(string(meta)?.parse()?),
+                _ => unreachable!(),
+            }
+            Ok(())
+        });
+
+        // A typestate builder changes type with every required field, so its
+        // setters can only ever take it by value.
+        let pattern = match pattern {
+            Some((Pattern::Mutable | Pattern::Immutable, lit)) if typestate.is_some() => {
+                errors.push(syn::Error::new_spanned(
+                    lit,
+                    "#[builder(typestate)] only supports `pattern = \"owned\"`",
+                ));
+                Pattern::Owned
+            }
+            Some((pattern, _)) => pattern,
+            None if typestate.is_some() => Pattern::Owned,
+            None => Pattern::Mutable,
+        };
+
+        // The fields of a typestate builder change type as they are set, so
+        // there is no single builder type to deserialize into.
+        if let (Some(_), Some(serde)) = (&typestate, &serde) {
+            errors.push(syn::Error::new_spanned(
+                serde,
+                "#[builder(serde)] is not supported together with #[builder(typestate)]",
+            ));
+        }
+
+        // Nor is there a Debug impl to leave out.
+        if let (Some(_), Some(no_debug)) = (&typestate, &no_debug) {
+            errors.push(syn::Error::new_spanned(
+                no_debug,
+                "#[builder(no_debug)] is not supported together with #[builder(typestate)]",
+            ));
+        }
+
+        ContainerAttrs {
+            typestate: typestate.is_some(),
+            default,
+            setter_into,
+            validate,
+            pattern,
+            serde,
+            name,
+            vis: vis.unwrap_or_else(|| syn::parse_quote!(pub)),
+            constructor,
+            no_std,
+            no_from,
+            no_debug: no_debug.is_some(),
+            build_error,
+        }
+    }
+}
+
+// Options given on a field with #[builder(...)].
+pub(crate) struct FieldAttrs {
+    pub(crate) each: Option<syn::Ident>,
+    // The setter of a tuple field, which has no name of its own.
+    pub(crate) name: Option<syn::Ident>,
+    // The setter of a named field, if it should differ from the field name.
+    pub(crate) rename: Option<syn::Ident>,
+    pub(crate) default: Option<syn::Expr>,
+    // async fn() -> Result<T, E> that build_async() awaits when the field is
+    // unset.
+    pub(crate) default_async: Option<syn::Path>,
+    pub(crate) into: bool,
+    // Option<T> fields already get a setter taking T; setter(strip_option)
+    // spells that out, and is rejected on any other field.
+    pub(crate) strip_option: Option<syn::Path>,
+    pub(crate) merge: Option<(syn::Path, Merge)>,
+    // The collection behind a field with `each`, when its type doesn't show it,
+    // as for a type alias.
+    pub(crate) collection: Option<syn::Type>,
+    pub(crate) sub_builder: Option<syn::Path>,
+    // Leave the field out of the builder and build it from its default.
+    pub(crate) skip: bool,
+    // Visibility of the setters, if it should differ from the builder's.
+    pub(crate) vis: Option<syn::Visibility>,
+    // Documentation of the setters, instead of the field's own.
+    pub(crate) doc: Option<syn::LitStr>,
+    // fn(&T) -> Result<(), String> that build() runs on the field's value.
+    pub(crate) validate: Option<syn::Path>,
+    // Keep the value out of the builder's Debug output.
+    pub(crate) secret: Option<syn::Path>,
+}
+
+impl FieldAttrs {
+    pub(crate) fn parse(field: &syn::Field, errors: &mut Errors) -> Self {
+        let mut attrs = FieldAttrs {
+            each: None,
+            name: None,
+            rename: None,
+            default: None,
+            default_async: None,
+            into: false,
+            strip_option: None,
+            merge: None,
+            collection: None,
+            sub_builder: None,
+            skip: false,
+            vis: None,
+            doc: None,
+            validate: None,
+            secret: None,
+        };
+        // Every key that was given, to check them against each other.
+        let mut given: Vec<(&str, syn::Path)> = Vec::new();
+
+        parse_keys(&field.attrs, FIELD_KEYS, errors, |key, meta, errors| {
+            given.push((key, meta.path.clone()));
+            match key {
+                "each" => attrs.each = Some(string(meta)?.parse()?),
+                "name" => attrs.name = Some(string(meta)?.parse()?),
+                "rename" => attrs.rename = Some(string(meta)?.parse()?),
+                // Either `default` alone, or `default = "expr"`.
+                "default" => {
+                    attrs.default = Some(if meta.input.peek(syn::Token![=]) {
+                        string(meta)?.parse()?
+                    } else {
+                        syn::parse_quote!(core::default::Default::default())
+                    });
+                }
+                "default_async" => attrs.default_async = Some(string(meta)?.parse()?),
+                "setter" => {
+                    for option in setter_options(meta, errors)? {
+                        if option.is_ident("into") {
+                            attrs.into = true;
+                        } else {
+                            attrs.strip_option = Some(option);
+                        }
+                    }
+                }
+                "skip" => {
+                    flag(meta)?;
+                    attrs.skip = true;
+                }
+                "vis" => attrs.vis = Some(string(meta)?.parse()?),
+                "doc" => attrs.doc = Some(string(meta)?),
+                "validate" => attrs.validate = Some(string(meta)?.parse()?),
+                "merge" => {
+                    let lit = string(meta)?;
+                    let merge = match lit.value().as_str() {
+                        "append" => Merge::Append,
+                        "replace" => Merge::Replace,
+                        _ => {
+                            return Err(syn::Error::new_spanned(
+                                lit,
+                                "expected `\"append\"` or `\"replace\"`",
+                            ));
+                        }
+                    };
+                    attrs.merge = Some((meta.path.clone(), merge));
+                }
+                "collection" => attrs.collection = Some(string(meta)?.parse()?),
+                "sub_builder" => attrs.sub_builder = Some(flag(meta)?),
+                "secret" => attrs.secret = Some(flag(meta)?),
+                _ => unreachable!(),
+            }
+            Ok(())
+        });
+
+        let position = |key: &str| given.iter().position(|(given, _)| *given == key);
+        for (first, second) in FIELD_CONFLICTS {
+            // Reported at whichever of the two keys comes last.
+            if let (Some(i), Some(j)) = (position(first), position(second)) {
+                let (earlier, (later, path)) =
+                    if i < j { (first, &given[j]) } else { (second, &given[i]) };
+                errors.push(syn::Error::new_spanned(
+                    path,
+                    format!("`{}` cannot be combined with `{}`", later, earlier),
+                ));
+            }
+        }
+        for key in ["merge", "collection"] {
+            if let (Some(i), None) = (position(key), position("each")) {
+                errors.push(syn::Error::new_spanned(
+                    &given[i].1,
+                    format!("#[builder({} = \"...\")] requires #[builder(each = \"...\")]", key),
+                ));
+            }
+        }
+
+        attrs
+    }
+}
+
+// Calls `parse_key` with each key of every #[builder(...)] among `attrs`.
+// Unknown and repeated keys are reported here, and whatever `parse_key`
+// finds wrong with the value of a known key is reported without giving up on
+// the keys after it.
+fn parse_keys(
+    attrs: &[syn::Attribute],
+    known: &[(&'static str, &str)],
+    errors: &mut Errors,
+    mut parse_key: impl FnMut(&'static str, &ParseNestedMeta, &mut Errors) -> syn::Result<()>,
+) {
+    let mut seen = Vec::new();
+
+    for attr in attrs {
+        if !attr.path().is_ident("builder") {
+            continue;
+        }
+
+        let parsed = attr.parse_nested_meta(|meta| {
+            let name = path_name(&meta.path);
+            let key = known.iter().map(|(key, _)| *key).find(|key| *key == name);
+            let result = match key {
+                None => Err(unknown_key(
+                    "builder attribute",
+                    "builder",
+                    &attr.meta,
+                    &meta.path,
+                    &name,
+                    known,
+                )),
+                Some(key) if seen.contains(&key) => Err(syn::Error::new_spanned(
+                    &meta.path,
+                    format!("duplicate builder attribute `{}`", key),
+                )),
+                Some(key) => {
+                    seen.push(key);
+                    parse_key(key, &meta, errors)
+                }
+            };
+            if let Err(error) = result {
+                errors.push(error);
+                skip_value(&meta)?;
+            }
+            Ok(())
+        });
+
+        // Only malformed syntax, such as a missing comma, gets here.
+        if let Err(error) = parsed {
+            errors.push(error);
+        }
+    }
+}
+
+// The options of setter(...), each checked like the keys of #[builder(...)].
+fn setter_options(meta: &ParseNestedMeta, errors: &mut Errors) -> syn::Result<Vec<syn::Path>> {
+    let mut options: Vec<syn::Path> = Vec::new();
+    meta.parse_nested_meta(|option| {
+        let name = path_name(&option.path);
+        let result = if !SETTER_KEYS.iter().any(|(key, _)| *key == name) {
+            Err(unknown_key(
+                "setter option",
+                "setter",
+                &option.path,
+                &option.path,
+                &name,
+                SETTER_KEYS,
+            ))
+        } else if options.iter().any(|seen| seen.is_ident(&name)) {
+            Err(syn::Error::new_spanned(
+                &option.path,
+                format!("duplicate setter option `{}`", name),
+            ))
+        } else {
+            flag(&option).map(|path| options.push(path))
+        };
+        if let Err(error) = result {
+            errors.push(error);
+            skip_value(&option)?;
+        }
+        Ok(())
+    })?;
+    Ok(options)
+}
+
+// A key such as `skip` that takes no value.
+fn flag(meta: &ParseNestedMeta) -> syn::Result<syn::Path> {
+    if meta.input.peek(syn::Token![=]) || meta.input.peek(syn::token::Paren) {
+        return Err(meta.error(format!("`{}` does not take a value", path_name(&meta.path))));
+    }
+    Ok(meta.path.clone())
+}
+
+// The string literal of a key such as `each = "..."`.
+fn string(meta: &ParseNestedMeta) -> syn::Result<syn::LitStr> {
+    let name = path_name(&meta.path);
+    if !meta.input.peek(syn::Token![=]) {
+        return Err(meta.error(format!("expected `{} = \"...\"`", name)));
+    }
+
+    let value: syn::Expr = meta.value()?.parse()?;
+    match value {
+        syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit), .. }) => Ok(lit),
+        value => Err(syn::Error::new_spanned(
+            value,
+            format!("expected a string literal, as in `{} = \"...\"`", name),
+        )),
+    }
+}
+
+// Skips what is left of a key's value, up to the comma before the next key,
+// so that parsing carries on after an error.
+fn skip_value(meta: &ParseNestedMeta) -> syn::Result<()> {
+    meta.input.step(|cursor| {
+        let mut rest = *cursor;
+        while let Some((token, next)) = rest.token_tree() {
+            match token {
+                TokenTree::Punct(punct) if punct.as_char() == ',' => break,
+                _ => rest = next,
+            }
+        }
+        Ok(((), rest))
+    })
+}
+
+// `what` is the kind of key, as in "unknown setter option `intoo`", and
+// `outer` the attribute it goes in, as in `setter(into)`. A misspelled key is
+// reported at `enclosing` with the key it was meant to be, and any other
+// unknown key at its own path with all the keys that would do.
+fn unknown_key(
+    what: &str,
+    outer: &str,
+    enclosing: &dyn ToTokens,
+    path: &syn::Path,
+    name: &str,
+    known: &[(&str, &str)],
+) -> syn::Error {
+    match closest_key(name, known) {
+        Some(usage) => {
+            syn::Error::new_spanned(enclosing, format!("expected `{}({})`", outer, usage))
+        }
+        None => {
+            let usages: Vec<String> =
+                known.iter().map(|(_, usage)| format!("`{}`", usage)).collect();
+            let message =
+                format!("unknown {} `{}`; expected one of {}", what, name, usages.join(", "));
+            syn::Error::new_spanned(path, message)
+        }
+    }
+}
+
+// How the known key closest to `name` is written, if `name` looks like a
+// misspelling of it: within one edit for every three characters.
+fn closest_key<'a>(name: &str, known: &[(&str, &'a str)]) -> Option<&'a str> {
+    known
+        .iter()
+        .map(|(key, usage)| (edit_distance(name, key), *usage))
+        .filter(|(distance, _)| *distance <= name.len().max(3) / 3)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, usage)| usage)
+}
+
+// Levenshtein distance: the number of single character insertions, deletions
+// and substitutions that turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a != *b);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+fn path_name(path: &syn::Path) -> String {
+    let segments: Vec<String> = path.segments.iter().map(|s| s.ident.to_string()).collect();
+    segments.join("::")
+}
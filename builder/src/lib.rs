@@ -1,56 +1,1749 @@
 use proc_macro::TokenStream;
-use quote::quote;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
 use syn::{parse_macro_input, Data, DeriveInput, Fields};
 
-#[proc_macro_derive(Builder)]
+use crate::attrs::{ContainerAttrs, Errors, FieldAttrs};
+
+mod attrs;
+mod typestate;
+
+#[proc_macro_derive(Builder, attributes(builder))]
 pub fn derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
-    let struct_name = input.ident;
 
-    let builder_name = syn::Ident::new(&format!("{}Builder", struct_name), struct_name.span());
+    match expand(input) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(e) => TokenStream::from(e.to_compile_error()),
+    }
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2, syn::Error> {
+    let type_name = &input.ident;
 
-    // Check the named fields is Struct
-    let fields = if let Data::Struct(ref data_struct) = input.data {
-        if let Fields::Named(ref fields) = data_struct.fields {
-            fields
-        } else {
-            unimplemented!("Not support for unnamed fields");
+    // Problems with the attributes of the struct and of every field are all
+    // reported together.
+    let mut errors = Errors::default();
+    let attrs = ContainerAttrs::parse(&input.attrs, &mut errors);
+
+    // A struct gets a single builder, an enum gets one builder per variant.
+    let targets = match &input.data {
+        Data::Struct(data) => {
+            vec![Target::new(type_name, &input.generics, None, &data.fields, &attrs, &mut errors)]
+        }
+        Data::Enum(data) => {
+            if let Some(default) = &attrs.default {
+                errors.push(syn::Error::new_spanned(
+                    default,
+                    "#[builder(default)] on an enum is not supported, put it on the fields instead",
+                ));
+            }
+            // Every variant has a builder and a constructor of its own, named
+            // after the variant.
+            for ident in attrs.name.iter().chain(&attrs.constructor) {
+                errors.push(syn::Error::new_spanned(
+                    ident,
+                    "#[builder(name = \"...\")] and #[builder(constructor = \"...\")] are not \
+                     supported on enums",
+                ));
+            }
+            data.variants
+                .iter()
+                .map(|variant| {
+                    let (generics, fields) = (&input.generics, &variant.fields);
+                    let variant = Some(&variant.ident);
+                    Target::new(type_name, generics, variant, fields, &attrs, &mut errors)
+                })
+                .collect()
+        }
+        Data::Union(data) => {
+            return Err(syn::Error::new_spanned(
+                data.union_token,
+                "Builder cannot be derived for unions",
+            ));
         }
-    } else {
-        unimplemented!("Only support for Struct");
     };
+    errors.finish()?;
 
-    // Define each builder Struct fields: Option<T> for the original type T
-    let builder_fields = fields.named.iter().map(|f| {
-        let name = &f.ident;
-        let ty = &f.ty;
-        quote! {
-            #name: std::option::Option<#ty>
+    let mut expanded = TokenStream2::new();
+    for target in &targets {
+        if attrs.typestate {
+            expanded.extend(typestate::expand(target));
+        } else {
+            expanded.extend(expand_target(target));
+        }
+    }
+
+    Ok(expanded)
+}
+
+// Generate the builder, its constructor on the input type and its error type.
+fn expand_target(target: &Target) -> TokenStream2 {
+    let Target { type_name, builder_name, error_name, constructor, path, generics, fields, .. } =
+        target;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let marker_field = target.marker_field();
+    let marker_init = target.marker_init();
+    let alloc = target.alloc();
+
+    // Define each builder Struct fields: Option<T> for the original type T.
+    // Repeated fields collect their items into an Option<C>, which is None
+    // until the first item arrives.
+    let serde_default = target.serde_attr(quote!(default));
+    let builder_fields = fields.iter().map(|f| {
+        let name = &f.name;
+        let storage = f.storage_ty();
+        match &f.kind {
+            FieldKind::SubBuilder { builder, .. } => {
+                // Deserializing starts from a fresh builder for the field.
+                let constructor = format!("<{}>::__new", quote!(#builder));
+                let serde_default = target.serde_attr(quote!(default = #constructor));
+                quote! { #serde_default #name: #storage }
+            }
+            _ => quote! { #serde_default #name: #storage },
         }
     });
 
     // Initialize each field with None in the builder() function
-    let builder_inits = fields.named.iter().map(|f| {
-        let name = &f.ident;
-        quote! {
-            #name: std::option::Option::None
+    let builder_inits = fields.iter().map(|f| {
+        let name = &f.name;
+        match &f.kind {
+            FieldKind::Required | FieldKind::Optional(_) | FieldKind::Repeated { .. } => quote! {
+                #name: core::option::Option::None
+            },
+            FieldKind::SubBuilder { builder, .. } => {
+                let new = f.nested_method("__new");
+                quote! { #name: <#builder>::#new() }
+            }
         }
     });
 
+    let pattern = target.attrs.pattern;
+    let setters = fields.iter().map(|f| f.setters(pattern));
+
+    // merge() keeps each field from `other` when it was set there, and from
+    // `self` otherwise.
+    let merged_fields = fields.iter().map(|f| {
+        let name = &f.name;
+        let value = f.merge_value();
+        quote! { #name: #value }
+    });
+
+    // Every required field that was never set is collected, so that the error
+    // lists all of them instead of only the first one. Sub-builders are built
+    // here as well, and their errors are collected along with the rest.
+    let missing_checks: Vec<TokenStream2> = fields
+        .iter()
+        .map(|f| {
+            let name = &f.name;
+            match f.kind {
+                FieldKind::SubBuilder { .. } => {
+                    let built = f.sub_builder_value();
+                    let variant = f.nested_variant();
+                    let build_nested = f.nested_method("__build_nested");
+                    quote! {
+                        let #built = match self.#name.#build_nested() {
+                            core::result::Result::Ok(value) => core::option::Option::Some(value),
+                            core::result::Result::Err(error) => {
+                                missing.push(#error_name::#variant(error));
+                                core::option::Option::None
+                            }
+                        };
+                    }
+                }
+                _ if f.is_required() => {
+                    let variant = f.missing_variant();
+                    quote! {
+                        if self.#name.is_none() {
+                            missing.push(#error_name::#variant);
+                        }
+                    }
+                }
+                _ => quote!(),
+            }
+        })
+        .collect();
+
+    let vis = &target.attrs.vis;
+    let error = error_enum(target, fields);
+    let build_error = target.build_error();
+
+    // An owned builder is consumed by build(). Otherwise the fields are cloned
+    // out, which needs every field type to be Clone; for field types involving
+    // generic parameters that has to be required explicitly.
+    let by_value = pattern == Pattern::Owned;
+    let build_receiver = if by_value { quote!(self) } else { quote!(&self) };
+    let clone_bounds: Vec<TokenStream2> = fields
+        .iter()
+        .filter(|f| !by_value && !f.is_sub_builder())
+        .map(|f| {
+            let ty = f.ty;
+            quote! { #ty: core::clone::Clone }
+        })
+        .chain(target.container_default_bounds())
+        .collect();
+    let build_async = build_async(target, &clone_bounds);
+
+    // __new() and __build_nested() are what an outer builder calls for a
+    // #[builder(sub_builder)] field, and are the same whatever the pattern,
+    // constructor and build error of this builder. A proc-macro crate cannot
+    // export a trait for them, so they are hidden inherent methods.
+    //
+    // __build_nested() always clones. Its bounds are under a binder, so that
+    // a field type that is not Clone only makes it unusable.
+    let nested_bounds: Vec<TokenStream2> = fields
+        .iter()
+        .filter(|f| !f.is_sub_builder())
+        .map(|f| {
+            let ty = f.ty;
+            quote! { #ty: core::clone::Clone }
+        })
+        .chain(target.container_default_bounds())
+        .map(|bound| quote!(for<'__builder> #bound))
+        .collect();
+
+    // The fields missing_fields() reports: those build() would complain about,
+    // with a sub-builder named as a whole if anything is missing inside it.
+    let missing_names = fields.iter().filter_map(|f| {
+        let name = &f.name;
+        let field_name = f.display_name();
+        let missing = match f.kind {
+            FieldKind::SubBuilder { .. } => quote!(!self.#name.missing_fields().is_empty()),
+            _ if f.is_required() => quote!(self.#name.is_none()),
+            _ => return None,
+        };
+        Some(quote! {
+            if #missing {
+                missing.push(#field_name);
+            }
+        })
+    });
+    let debug = (!target.attrs.no_debug).then(|| debug_impl(target));
+
+    // The body of build(), and of __build_nested() which always borrows the
+    // builder. Every error goes through From::from, so the same body returns
+    // either error type.
+    let container_default = target.container_default();
+    let validation = target.validation();
+    let build_body = |by_value: bool| {
+        let skipped_fields = target.skipped_fields();
+        let build_fields = fields.iter().map(|f| {
+            let member = &f.member;
+            let value = f.validated(f.build_value(by_value), target);
+            quote! { #member: #value }
+        });
+        quote! {
+            let mut missing: #alloc::vec::Vec<#error_name> = #alloc::vec::Vec::new();
+            #(#missing_checks)*
+            if missing.len() == 1 {
+                return core::result::Result::Err(core::convert::From::from(missing.remove(0)));
+            }
+            if !missing.is_empty() {
+                let error = #error_name::Multiple(missing);
+                return core::result::Result::Err(core::convert::From::from(error));
+            }
+
+            #container_default
+            let value = #path {
+                #(#build_fields,)*
+                #(#skipped_fields,)*
+            };
+            #validation
+            core::result::Result::Ok(value)
+        }
+    };
+    let nested_body = build_body(false);
+    let build_body = if by_value {
+        build_body(true)
+    } else {
+        quote!(core::result::Result::map_err(Self::__build_nested(self), core::convert::From::from))
+    };
+
+    let conversions = target.conversions(quote!(#builder_name #ty_generics), BuilderField::store);
+
+    // Immutable setters start from a copy of the builder, and so does an
+    // immutable outer builder from a copy of its sub-builders.
+    let clone = clone_impl(target);
+    let derive_deserialize =
+        target.attrs.serde.is_some().then(|| quote!(#[derive(serde::Deserialize)]));
+
     // Generate code
-    let expanded = quote! {
-        pub struct #builder_name {
+    quote! {
+        #derive_deserialize
+        #vis struct #builder_name #generics #where_clause {
             #(#builder_fields,)*
+            #marker_field
         }
 
-        impl #struct_name {
-            pub fn builder() -> #builder_name {
+        impl #impl_generics #type_name #ty_generics #where_clause {
+            #vis fn #constructor() -> #builder_name #ty_generics {
+                #builder_name::__new()
+            }
+        }
+
+        impl #impl_generics #builder_name #ty_generics #where_clause {
+            #(#setters)*
+
+            #vis fn merge(self, other: Self) -> Self {
+                #builder_name {
+                    #(#merged_fields,)*
+                    #marker_init
+                }
+            }
+
+            #vis fn build(
+                #build_receiver,
+            ) -> core::result::Result<#type_name #ty_generics, #build_error>
+            where
+                #(#clone_bounds,)*
+            {
+                #build_body
+            }
+
+            #build_async
+
+            #[doc(hidden)]
+            #vis fn __new() -> Self {
                 #builder_name {
                     #(#builder_inits,)*
+                    #marker_init
+                }
+            }
+
+            #[doc(hidden)]
+            #vis fn __build_nested(
+                &self,
+            ) -> core::result::Result<#type_name #ty_generics, #error_name>
+            where
+                #(#nested_bounds,)*
+            {
+                #nested_body
+            }
+
+            /// The required fields that have not been set yet.
+            #vis fn missing_fields(&self) -> #alloc::vec::Vec<&'static str> {
+                let mut missing = #alloc::vec::Vec::new();
+                #(#missing_names)*
+                missing
+            }
+        }
+
+        #clone
+
+        #debug
+
+        #conversions
+
+        #error
+    }
+}
+
+// Debug for the builder, showing which fields have been set and to what. The
+// values of #[builder(secret)] fields are shown as <redacted>. Left out with
+// #[builder(no_debug)].
+//
+// The bounds are on the types stored in the builder, under a binder so that a
+// field type that is not Debug only leaves the builder without a Debug impl.
+fn debug_impl(target: &Target) -> TokenStream2 {
+    let Target { builder_name, generics, fields, .. } = target;
+    let mut generics = (*generics).clone();
+    let bounds = generics.make_where_clause();
+    for f in fields.iter().filter(|f| !f.secret) {
+        let storage = f.storage_ty();
+        bounds.predicates.push(syn::parse_quote!(for<'__builder> #storage: core::fmt::Debug));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let builder_string = builder_name.to_string();
+    let debug_fields = fields.iter().map(|f| {
+        let name = &f.name;
+        let field_name = f.display_name();
+        match f.kind {
+            _ if !f.secret => quote! {
+                debug.field(#field_name, &self.#name);
+            },
+            FieldKind::Required | FieldKind::Optional(_) => quote! {
+                match &self.#name {
+                    core::option::Option::Some(_) => {
+                        debug.field(#field_name, &core::format_args!("Some(<redacted>)"))
+                    }
+                    core::option::Option::None => {
+                        debug.field(#field_name, &core::format_args!("None"))
+                    }
+                };
+            },
+            FieldKind::Repeated { .. } | FieldKind::SubBuilder { .. } => quote! {
+                debug.field(#field_name, &core::format_args!("<redacted>"));
+            },
+        }
+    });
+
+    quote! {
+        impl #impl_generics core::fmt::Debug for #builder_name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                let mut debug = f.debug_struct(#builder_string);
+                #(#debug_fields)*
+                debug.finish()
+            }
+        }
+    }
+}
+
+// Clone for the builder, whatever its pattern. Like Debug it is bounded on the
+// storage of each field rather than on the type parameters, so a field type
+// that is not Clone only leaves the builder without it.
+fn clone_impl(target: &Target) -> TokenStream2 {
+    let Target { builder_name, generics, fields, .. } = target;
+    let mut generics = (*generics).clone();
+    let bounds = generics.make_where_clause();
+    for f in fields.iter() {
+        let storage = f.storage_ty();
+        bounds.predicates.push(syn::parse_quote!(for<'__builder> #storage: core::clone::Clone));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let cloned_fields = fields.iter().map(|f| {
+        let name = &f.name;
+        quote! { #name: core::clone::Clone::clone(&self.#name) }
+    });
+    let marker_init = target.marker_init();
+
+    quote! {
+        impl #impl_generics core::clone::Clone for #builder_name #ty_generics #where_clause {
+            fn clone(&self) -> Self {
+                #builder_name {
+                    #(#cloned_fields,)*
+                    #marker_init
+                }
+            }
+        }
+    }
+}
+
+// build_async(), for a builder with #[builder(default_async = "...")] fields.
+// It awaits the initializers of all unset fields concurrently, stores what
+// they produce in the builder, and then finishes with build(). The futures are
+// polled in turn by a join written out here, so no executor or async runtime
+// is needed beyond whatever awaits build_async().
+fn build_async(target: &Target, clone_bounds: &[TokenStream2]) -> Option<TokenStream2> {
+    let initialized: Vec<&BuilderField> =
+        target.fields.iter().filter(|f| f.default_async.is_some()).collect();
+    if initialized.is_empty() {
+        return None;
+    }
+
+    let Target { type_name, error_name, generics, .. } = target;
+    let (_, ty_generics, _) = generics.split_for_impl();
+    let vis = &target.attrs.vis;
+    let build_error = target.build_error();
+    let alloc = target.alloc();
+    let (receiver, builder) = target.attrs.pattern.local_builder();
+
+    // Each initializer only runs if its field is unset, and its output is
+    // kept until every initializer has finished.
+    let futures = initialized.iter().map(|f| {
+        let name = &f.name;
+        let default_async = &f.default_async;
+        let (future, output) = f.initializer_locals();
+        quote! {
+            let #future = {
+                let unset = __builder.#name.is_none();
+                async move {
+                    if unset {
+                        core::option::Option::Some(#default_async().await)
+                    } else {
+                        core::option::Option::None
+                    }
+                }
+            };
+            let mut #future = core::pin::pin!(#future);
+            let mut #output = core::option::Option::None;
+        }
+    });
+    let polls = initialized.iter().map(|f| {
+        let (future, output) = f.initializer_locals();
+        quote! {
+            if #output.is_none() {
+                match core::future::Future::poll(#future.as_mut(), cx) {
+                    core::task::Poll::Ready(value) => #output = core::option::Option::Some(value),
+                    core::task::Poll::Pending => pending = true,
+                }
+            }
+        }
+    });
+    let stores = initialized.iter().map(|f| {
+        let name = &f.name;
+        let field_name = f.display_name();
+        let (_, output) = f.initializer_locals();
+        quote! {
+            if let core::option::Option::Some(core::option::Option::Some(result)) = #output {
+                match result {
+                    core::result::Result::Ok(value) => {
+                        __builder.#name = core::option::Option::Some(value);
+                    }
+                    core::result::Result::Err(error) => {
+                        let message = #alloc::format!("{}: {}", #field_name, error);
+                        failed.push(#error_name::Initialization(message));
+                    }
+                }
+            }
+        }
+    });
+
+    Some(quote! {
+        #vis async fn build_async(
+            #receiver,
+        ) -> core::result::Result<#type_name #ty_generics, #build_error>
+        where
+            #(#clone_bounds,)*
+        {
+            #builder
+            #(#futures)*
+            core::future::poll_fn(|cx| {
+                let mut pending = false;
+                #(#polls)*
+                if pending {
+                    core::task::Poll::Pending
+                } else {
+                    core::task::Poll::Ready(())
+                }
+            })
+            .await;
+
+            let mut failed: #alloc::vec::Vec<#error_name> = #alloc::vec::Vec::new();
+            #(#stores)*
+            if failed.len() == 1 {
+                return core::result::Result::Err(core::convert::From::from(failed.remove(0)));
+            }
+            if !failed.is_empty() {
+                let error = #error_name::Multiple(failed);
+                return core::result::Result::Err(core::convert::From::from(error));
+            }
+            __builder.build()
+        }
+    })
+}
+
+// The error returned by build(): one Missing<Field> variant per required field,
+// one Nested<Field> variant per sub-builder, a Validation variant, and Multiple
+// when several fields are missing or fail to build at once.
+//
+// Fields missing from a sub-builder are reported with their path from the
+// outer builder, as in `missing field: tls.cert_path`.
+fn error_enum(target: &Target, fields: &[BuilderField]) -> TokenStream2 {
+    let Target { error_name, .. } = target;
+    let vis = &target.attrs.vis;
+    let alloc = target.alloc();
+    let required = fields.iter().filter(|f| f.is_required());
+    let nested = fields.iter().filter_map(|f| match &f.kind {
+        FieldKind::SubBuilder { error, .. } => Some((f, error)),
+        _ => None,
+    });
+
+    let missing_variants = required.clone().map(|f| {
+        let variant = f.missing_variant();
+        let doc = format!("The `{}` field was never set.", f.display_name());
+        quote! {
+            #[doc = #doc]
+            #variant
+        }
+    });
+    let nested_variants = nested.clone().map(|(f, error)| {
+        let variant = f.nested_variant();
+        let doc = format!("The builder of the `{}` field failed.", f.display_name());
+        quote! {
+            #[doc = #doc]
+            #variant(#error)
+        }
+    });
+
+    let missing_fields = required.map(|f| {
+        let variant = f.missing_variant();
+        let field_name = f.display_name();
+        quote! {
+            #error_name::#variant => {
+                fields.push(#alloc::string::String::from(#field_name));
+            }
+        }
+    });
+    let nested_fields = nested.clone().map(|(f, _)| {
+        let variant = f.nested_variant();
+        let prefix = format!("{}.", f.display_name());
+        quote! {
+            #error_name::#variant(error) => {
+                for field in error.missing_fields() {
+                    fields.push(#alloc::format!("{}{}", #prefix, field));
                 }
             }
         }
+    });
+
+    // Only builders with async defaults can fail to initialize a field.
+    let initialization = fields.iter().any(|f| f.default_async.is_some());
+    let initialization_variant = initialization.then(|| {
+        quote! {
+            /// An async default of an unset field failed.
+            Initialization(#alloc::string::String),
+        }
+    });
+    let initialization_fields = initialization.then(|| {
+        quote! {
+            #error_name::Initialization(_) => {}
+        }
+    });
+    let initialization_message = initialization.then(|| {
+        quote! {
+            #error_name::Initialization(message) => {
+                core::write!(f, "initialization failed: {}", message)
+            }
+        }
+    });
+
+    // A sub-builder that failed for any other reason than a missing field
+    // shows its own message after the field name.
+    let nested_messages = nested.map(|(f, _)| {
+        let variant = f.nested_variant();
+        let field_name = f.display_name();
+        quote! {
+            #error_name::#variant(error) if error.missing_fields().is_empty() => {
+                core::write!(f, "{}: {}", #field_name, error)
+            }
+        }
+    });
+
+    quote! {
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #vis enum #error_name {
+            #(#missing_variants,)*
+            #(#nested_variants,)*
+            #initialization_variant
+            /// The built value was rejected by a validation check.
+            Validation(#alloc::string::String),
+            /// More than one field was never set or failed to build.
+            Multiple(#alloc::vec::Vec<#error_name>),
+        }
+
+        impl #error_name {
+            /// The path of every required field that was never set, such as
+            /// `tls.cert_path` for a field of a sub-builder.
+            #vis fn missing_fields(&self) -> #alloc::vec::Vec<#alloc::string::String> {
+                let mut fields = #alloc::vec::Vec::new();
+                match self {
+                    #(#missing_fields)*
+                    #(#nested_fields)*
+                    #initialization_fields
+                    #error_name::Validation(_) => {}
+                    #error_name::Multiple(errors) => {
+                        for error in errors {
+                            fields.extend(error.missing_fields());
+                        }
+                    }
+                }
+                fields
+            }
+        }
+
+        impl core::fmt::Display for #error_name {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                match self {
+                    #error_name::Validation(message) => {
+                        core::write!(f, "validation failed: {}", message)
+                    }
+                    #(#nested_messages)*
+                    #initialization_message
+                    error => {
+                        let fields = error.missing_fields();
+                        match fields.len() {
+                            0 => {}
+                            1 => core::write!(f, "missing field: {}", fields[0])?,
+                            _ => core::write!(f, "missing fields: {}", fields.join(", "))?,
+                        }
+                        let mut separator = if fields.is_empty() { "" } else { "; " };
+                        if let #error_name::Multiple(errors) = error {
+                            for error in errors {
+                                if error.missing_fields().is_empty() {
+                                    core::write!(f, "{}{}", separator, error)?;
+                                    separator = "; ";
+                                }
+                            }
+                        }
+                        core::result::Result::Ok(())
+                    }
+                }
+            }
+        }
+
+        impl core::error::Error for #error_name {}
+    }
+}
+
+// How setters and build() take the builder, from #[builder(pattern = "...")].
+#[derive(Clone, Copy, PartialEq)]
+enum Pattern {
+    // Setters take and return &mut Self, build(&self) clones the fields out.
+    Mutable,
+    // Setters take and return Self, build(self) moves the fields out, so no
+    // field needs to be Clone.
+    Owned,
+    // Setters take &self and return an updated copy of the builder,
+    // build(&self) clones the fields out.
+    Immutable,
+}
+
+impl Pattern {
+    // A setter method whose `body` updates a local `builder`, which is the
+    // builder itself or a copy of it depending on the pattern. `head` is what
+    // goes before `fn`: the doc attributes and the visibility.
+    fn setter(
+        self,
+        head: TokenStream2,
+        method: &syn::Ident,
+        generics: Option<TokenStream2>,
+        args: TokenStream2,
+        body: TokenStream2,
+    ) -> TokenStream2 {
+        let (receiver, builder) = self.local_builder();
+        let output = match self {
+            Pattern::Mutable => quote!(&mut Self),
+            Pattern::Owned | Pattern::Immutable => quote!(Self),
+        };
+        quote! {
+            #head fn #method #generics(#receiver, #args) -> #output {
+                #builder
+                #body
+                __builder
+            }
+        }
+    }
+
+    // The receiver of a method that updates the builder, and the statement
+    // that binds the builder it updates to a local `__builder`, which no
+    // argument named after a field can shadow.
+    fn local_builder(self) -> (TokenStream2, TokenStream2) {
+        match self {
+            Pattern::Mutable => (quote!(&mut self), quote!(let __builder = self;)),
+            Pattern::Owned => (quote!(self), quote!(let mut __builder = self;)),
+            Pattern::Immutable => {
+                (quote!(&self), quote!(let mut __builder = core::clone::Clone::clone(self);))
+            }
+        }
+    }
+}
+
+// One builder to generate: for the input struct, or for a single variant of
+// the input enum.
+struct Target<'a> {
+    type_name: &'a syn::Ident,
+    // CommandBuilder, or ShapeCircleBuilder for the Circle variant of Shape.
+    builder_name: syn::Ident,
+    error_name: syn::Ident,
+    // builder, or circle_builder for the Circle variant of Shape.
+    constructor: syn::Ident,
+    // The path used to construct the value: Command, or Shape::Circle.
+    path: TokenStream2,
+    // The enum variant this builder is for, if any.
+    variant: Option<&'a syn::Ident>,
+    // Generics of the input type, which the builder carries over unchanged.
+    generics: &'a syn::Generics,
+    fields: Vec<BuilderField<'a>>,
+    // Fields marked #[builder(skip)], with the value build() gives them.
+    skipped: Vec<(syn::Member, TokenStream2)>,
+    // The types of the fields that build() may clone out of the struct's own
+    // Default value, under #[builder(default)].
+    default_clones: Vec<&'a syn::Type>,
+    attrs: &'a ContainerAttrs,
+}
+
+impl<'a> Target<'a> {
+    fn new(
+        type_name: &'a syn::Ident,
+        generics: &'a syn::Generics,
+        variant: Option<&'a syn::Ident>,
+        fields: &'a Fields,
+        attrs: &'a ContainerAttrs,
+        errors: &mut Errors,
+    ) -> Self {
+        let (builder_name, constructor, path) = match variant {
+            None => (
+                attrs.name.clone().unwrap_or_else(|| {
+                    format_ident!("{}Builder", type_name, span = type_name.span())
+                }),
+                attrs.constructor.clone().unwrap_or_else(|| format_ident!("builder")),
+                quote!(#type_name),
+            ),
+            Some(variant) => (
+                format_ident!("{}{}Builder", type_name, variant, span = variant.span()),
+                format_ident!("{}_builder", snake_case(&variant.unraw().to_string())),
+                quote!(#type_name::#variant),
+            ),
+        };
+        let error_name = format_ident!("{}Error", builder_name);
+
+        // Skipped fields are left out of the builder and only built from
+        // their default, or the struct's, or Default::default(). A field is
+        // cloned out of the struct's default rather than moved, since a type
+        // that implements Drop cannot be taken apart.
+        let container_default = |member: &syn::Member| {
+            attrs.default.as_ref().map(|_| quote!(core::clone::Clone::clone(&__default.#member)))
+        };
+        let mut builder_fields = Vec::new();
+        let mut skipped = Vec::new();
+        let mut default_clones = Vec::new();
+        for (index, field) in fields.iter().enumerate() {
+            let field_attrs = FieldAttrs::parse(field, errors);
+            if field_attrs.skip {
+                let member = match &field.ident {
+                    Some(ident) => syn::Member::Named(ident.clone()),
+                    None => syn::Member::Unnamed(syn::Index::from(index)),
+                };
+                let default = match (field_attrs.default, container_default(&member)) {
+                    (Some(default), _) => quote!(#default),
+                    (None, Some(default)) => {
+                        default_clones.push(&field.ty);
+                        default
+                    }
+                    (None, None) => quote!(core::default::Default::default()),
+                };
+                skipped.push((member, default));
+                continue;
+            }
+            match BuilderField::new(index, field, field_attrs, attrs) {
+                Ok(field) => builder_fields.push(field),
+                Err(error) => errors.push(error),
+            }
+        }
+
+        for field in &mut builder_fields {
+            if field.default.is_none() && !field.is_sub_builder() {
+                field.default = container_default(&field.member);
+                if field.default.is_some() {
+                    default_clones.push(field.ty);
+                }
+            }
+        }
+
+        // A setter named like one of the builder's own methods would be a
+        // second definition of it.
+        let mut reserved = vec!["build"];
+        if !attrs.typestate {
+            reserved.extend(["merge", "missing_fields", "__new", "__build_nested"]);
+        }
+        if builder_fields.iter().any(|f| f.default_async.is_some()) {
+            reserved.push("build_async");
+        }
+        for field in &builder_fields {
+            for (method, key) in field.setter_names() {
+                let method_name = method.unraw().to_string();
+                if reserved.contains(&method_name.as_str()) {
+                    errors.push(syn::Error::new_spanned(
+                        method,
+                        format!(
+                            "the setter `{0}` would clash with the builder's own `{0}()`; \
+                             name it differently with #[builder({1} = \"...\")]",
+                            method_name, key,
+                        ),
+                    ));
+                }
+            }
+        }
+
+        Target {
+            type_name,
+            builder_name,
+            error_name,
+            constructor,
+            path,
+            variant,
+            generics,
+            fields: builder_fields,
+            skipped,
+            default_clones,
+            attrs,
+        }
+    }
+
+    // impl From<Command> for CommandBuilder, unless #[builder(no_from)], and
+    // Command::to_builder() which clones the fields of an existing value into
+    // a new builder, so a value can be tweaked and rebuilt. `builder_ty` is
+    // the builder type the value converts into, and `store` turns a field's
+    // value into what the builder keeps for it. Not generated for enum
+    // variants, since a value of the enum may be any of its variants.
+    fn conversions(
+        &self,
+        builder_ty: TokenStream2,
+        store: impl Fn(&BuilderField<'a>, TokenStream2) -> TokenStream2,
+    ) -> Option<TokenStream2> {
+        if self.variant.is_some() {
+            return None;
+        }
+
+        let Target { type_name, builder_name, path, generics, fields, .. } = self;
+        let vis = &self.attrs.vis;
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        let marker_init = self.marker_init();
+
+        // `host: host` would trip the non_shorthand_field_patterns lint.
+        let bindings = fields.iter().map(|f| {
+            let member = &f.member;
+            let name = &f.name;
+            match member {
+                syn::Member::Named(ident) if ident == name => quote! { #name },
+                _ => quote! { #member: #name },
+            }
+        });
+        let skipped = self.skipped.iter().map(|(member, _)| quote! { #member: _ });
+        let moved_fields = fields.iter().map(|f| {
+            let name = &f.name;
+            let value = store(f, quote!(#name));
+            quote! { #name: #value }
+        });
+        // A sub-builder is converted from the field's value in turn, which
+        // its type may leave out with #[builder(no_from)]. As with the Clone
+        // bounds below, the binder only leaves the conversion unusable then.
+        let from_bounds: Vec<syn::WherePredicate> = fields
+            .iter()
+            .filter_map(|f| match &f.kind {
+                FieldKind::SubBuilder { builder, .. } => {
+                    let ty = f.ty;
+                    Some(syn::parse_quote!(for<'__builder> #builder: core::convert::From<#ty>))
+                }
+                _ => None,
+            })
+            .collect();
+        let mut from_generics = (*generics).clone();
+        from_generics.make_where_clause().predicates.extend(from_bounds.iter().cloned());
+        let from_where_clause = &from_generics.where_clause;
+        let from = (!self.attrs.no_from).then(|| {
+            quote! {
+                impl #impl_generics core::convert::From<#type_name #ty_generics> for #builder_ty
+                #from_where_clause
+                {
+                    fn from(value: #type_name #ty_generics) -> Self {
+                        let #path { #(#bindings,)* #(#skipped,)* } = value;
+                        #builder_name {
+                            #(#moved_fields,)*
+                            #marker_init
+                        }
+                    }
+                }
+            }
+        });
+        let cloned_fields = fields.iter().map(|f| {
+            let name = &f.name;
+            let member = &f.member;
+            let value = store(f, quote!(core::clone::Clone::clone(&self.#member)));
+            quote! { #name: #value }
+        });
+        // The binder keeps a bound on a concrete type that is not Clone from
+        // being an error, so to_builder() simply cannot be called in that case.
+        let clone_bounds = fields.iter().map(|f| {
+            let ty = f.ty;
+            quote! { for<'__builder> #ty: core::clone::Clone }
+        });
+
+        Some(quote! {
+            #from
+
+            impl #impl_generics #type_name #ty_generics #where_clause {
+                #vis fn to_builder(&self) -> #builder_ty
+                where
+                    #(#clone_bounds,)*
+                    #(#from_bounds,)*
+                {
+                    #builder_name {
+                        #(#cloned_fields,)*
+                        #marker_init
+                    }
+                }
+            }
+        })
+    }
+
+    // Fields left unset under #[builder(default)] on the struct are cloned out
+    // of the struct's own Default value.
+    fn container_default(&self) -> Option<TokenStream2> {
+        self.attrs.default.as_ref()?;
+
+        let type_name = self.type_name;
+        let (_, ty_generics, _) = self.generics.split_for_impl();
+        Some(quote! {
+            let __default: #type_name #ty_generics = core::default::Default::default();
+        })
+    }
+
+    // Hand the built `value` to #[builder(validate = "...")] before returning
+    // it, turning a rejection into the Validation error.
+    fn validation(&self) -> Option<TokenStream2> {
+        let validate = self.attrs.validate.as_ref()?;
+        let error_name = &self.error_name;
+        Some(quote! {
+            if let core::result::Result::Err(message) = #validate(&value) {
+                let error = #error_name::Validation(message);
+                return core::result::Result::Err(core::convert::From::from(error));
+            }
+        })
+    }
+
+    // The error type of build(): the builder's own, or the one given with
+    // #[builder(build_error = "...")] that the builder's converts into.
+    fn build_error(&self) -> TokenStream2 {
+        match &self.attrs.build_error {
+            Some(build_error) => quote!(#build_error),
+            None => {
+                let error_name = &self.error_name;
+                quote!(#error_name)
+            }
+        }
+    }
+
+    // Whether build() has anything to check once the value is complete.
+    fn validates(&self) -> bool {
+        self.attrs.validate.is_some() || self.fields.iter().any(|f| f.validate.is_some())
+    }
+
+    // The bounds build() needs to make the struct's default and clone fields
+    // out of it, which only hold for some arguments of a generic struct.
+    fn container_default_bounds(&self) -> Vec<TokenStream2> {
+        if self.attrs.default.is_none() {
+            return Vec::new();
+        }
+
+        let type_name = self.type_name;
+        let (_, ty_generics, _) = self.generics.split_for_impl();
+        let clones = self.default_clones.iter().map(|ty| quote! { #ty: core::clone::Clone });
+        std::iter::once(quote! { #type_name #ty_generics: core::default::Default })
+            .chain(clones)
+            .collect()
+    }
+
+    fn skipped_fields(&self) -> impl Iterator<Item = TokenStream2> + '_ {
+        self.skipped.iter().map(|(member, default)| quote! { #member: #default })
+    }
+
+    // Where the generated code takes Vec, String and format! from. Everything
+    // else comes from core, which every crate has.
+    fn alloc(&self) -> TokenStream2 {
+        if self.attrs.no_std { quote!(alloc) } else { quote!(std) }
+    }
+
+    // A builder for one enum variant may not mention every generic parameter
+    // of the enum, so a generic builder always carries a PhantomData of the
+    // type it builds. `fn() -> T` gives the builder the same variance as the
+    // built type without affecting Send, Sync or drop check.
+    fn marker_field(&self) -> Option<TokenStream2> {
+        if self.generics.params.is_empty() {
+            return None;
+        }
+
+        let type_name = self.type_name;
+        let (_, ty_generics, _) = self.generics.split_for_impl();
+        let serde_skip = self.serde_attr(quote!(skip));
+        Some(quote! {
+            #serde_skip
+            __marker: core::marker::PhantomData<fn() -> #type_name #ty_generics>,
+        })
+    }
+
+    // #[serde(...)] on a field of a builder that derives Deserialize. Every
+    // field is #[serde(default)], so a partial config file leaves the rest of
+    // the builder unset.
+    fn serde_attr(&self, attr: TokenStream2) -> Option<TokenStream2> {
+        self.attrs.serde.as_ref()?;
+        Some(quote!(#[serde(#attr)]))
+    }
+
+    fn marker_init(&self) -> Option<TokenStream2> {
+        if self.generics.params.is_empty() {
+            return None;
+        }
+
+        Some(quote! {
+            __marker: core::marker::PhantomData,
+        })
+    }
+}
+
+// A field of the input struct, together with how its builder method behaves.
+struct BuilderField<'a> {
+    // Name of the setter and of the builder's field: the field name, or `_0`,
+    // `_1`, ... (unless renamed with #[builder(name = "...")]) in a tuple.
+    name: syn::Ident,
+    // The field in the constructed value, so tuple fields are built as `{ 0: ... }`.
+    member: syn::Member,
+    ty: &'a syn::Type,
+    kind: FieldKind<'a>,
+    // Value used by build() when the field was never set, from
+    // #[builder(default)] or #[builder(default = "...")].
+    default: Option<TokenStream2>,
+    // Whether the setters are generic over Into<T>, from setter(into).
+    into: bool,
+    // Visibility of the setters: the builder's, unless given with
+    // #[builder(vis = "...")] on the field.
+    vis: syn::Visibility,
+    // Doc attributes put on the setters.
+    docs: Vec<TokenStream2>,
+    // Check that build() runs on the field's value, from
+    // #[builder(validate = "...")].
+    validate: Option<syn::Path>,
+    // Initializer that build_async() awaits if the field is unset, from
+    // #[builder(default_async = "...")].
+    default_async: Option<syn::Path>,
+    // Whether the builder's Debug output hides the value, from
+    // #[builder(secret)].
+    secret: bool,
+}
+
+enum FieldKind<'a> {
+    // Must be set before build() succeeds.
+    Required,
+    // Option<T> field: the setter takes T and the field may be left unset.
+    Optional(&'a syn::Type),
+    // Collection field with #[builder(each = "...")]: items are added one by
+    // one. Any collection that is Default and Extend<item> can be built this
+    // way. For an Option<C> field, `optional` is C, and the field stays None
+    // until the first item is added.
+    Repeated { each: syn::Ident, items: Items, merge: Merge, optional: Option<&'a syn::Type> },
+    // Field whose type derives Builder too, with #[builder(sub_builder)]: the
+    // builder holds a TlsConfigBuilder for a TlsConfig field, and builds it
+    // along with the outer value.
+    SubBuilder { builder: Box<syn::Type>, error: syn::Path },
+}
+
+// What the one-at-a-time setter of a collection field takes.
+enum Items {
+    // A single item: T for Vec<T>, HashSet<T>, SmallVec<[T; N]> and the like,
+    // and <C as IntoIterator>::Item for a collection C that isn't recognized.
+    Single(Box<syn::Type>),
+    // A key and a value for HashMap<K, V> and BTreeMap<K, V>.
+    Map(Box<syn::Type>, Box<syn::Type>),
+}
+
+impl Items {
+    // The type that the collection extends with.
+    fn item(&self) -> TokenStream2 {
+        match self {
+            Items::Single(item) => quote!(#item),
+            Items::Map(key, value) => quote!((#key, #value)),
+        }
+    }
+}
+
+// How merge() combines the items of a repeated field, from
+// #[builder(merge = "...")].
+#[derive(Clone, Copy)]
+enum Merge {
+    // The items of `other` follow those of `self`.
+    Append,
+    // The items of `other` are kept instead, unless it was never given any.
+    Replace,
+}
+
+impl<'a> BuilderField<'a> {
+    fn new(
+        index: usize,
+        field: &'a syn::Field,
+        attrs: FieldAttrs,
+        container: &ContainerAttrs,
+    ) -> Result<Self, syn::Error> {
+        let ty = &field.ty;
+
+        let (name, member) = match (&field.ident, attrs.name) {
+            (Some(ident), None) => {
+                let name = attrs.rename.unwrap_or_else(|| ident.clone());
+                (name, syn::Member::Named(ident.clone()))
+            }
+            (Some(_), Some(name)) => {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "#[builder(name = \"...\")] is only supported on tuple fields",
+                ));
+            }
+            (None, name) => {
+                let span = ty.span();
+                let name = name
+                    .or(attrs.rename)
+                    .unwrap_or_else(|| format_ident!("_{}", index, span = span));
+                (name, syn::Member::Unnamed(syn::Index { index: index as u32, span }))
+            }
+        };
+
+        let kind = if let Some(each) = attrs.each {
+            // A typestate builder has no merge().
+            let merge = match attrs.merge {
+                Some((path, _)) if container.typestate => {
+                    return Err(syn::Error::new_spanned(
+                        path,
+                        "#[builder(merge = \"...\")] is not supported with #[builder(typestate)]",
+                    ));
+                }
+                Some((_, merge)) => merge,
+                None => Merge::Append,
+            };
+            let optional = generic_argument(ty, "Option");
+            // The items come from #[builder(collection = "...")] if given,
+            // since an alias doesn't say what it stands for.
+            let collection = attrs.collection.as_ref().or(optional).unwrap_or(ty);
+            FieldKind::Repeated { each, items: collection_items(collection), merge, optional }
+        } else if let Some(sub_builder) = attrs.sub_builder {
+            if container.typestate {
+                return Err(syn::Error::new_spanned(
+                    sub_builder,
+                    "#[builder(sub_builder)] is not supported with #[builder(typestate)]",
+                ));
+            }
+            let (builder, error) = sub_builder_types(ty)?;
+            FieldKind::SubBuilder { builder, error }
+        } else if let Some(inner) = generic_argument(ty, "Option") {
+            FieldKind::Optional(inner)
+        } else if let Some(strip_option) = attrs.strip_option {
+            return Err(syn::Error::new_spanned(
+                strip_option,
+                "setter(strip_option) requires a field of type Option<T>",
+            ));
+        } else {
+            FieldKind::Required
+        };
+
+        // Nor a Debug impl to keep the value out of.
+        if let Some(secret) = &attrs.secret {
+            if container.typestate {
+                return Err(syn::Error::new_spanned(
+                    secret,
+                    "#[builder(secret)] is not supported with #[builder(typestate)]",
+                ));
+            }
+        }
+
+        // An async default fills in a field that would otherwise be missing, so
+        // only fields that are required can have one.
+        if let Some(default_async) = &attrs.default_async {
+            if container.typestate {
+                return Err(syn::Error::new_spanned(
+                    default_async,
+                    "#[builder(default_async = \"...\")] is not supported with \
+                     #[builder(typestate)]",
+                ));
+            }
+            if !matches!(kind, FieldKind::Required) {
+                return Err(syn::Error::new_spanned(
+                    default_async,
+                    "#[builder(default_async = \"...\")] is not supported on Option<T> fields",
+                ));
+            }
+        }
+
+        let default = attrs.default.map(|default| quote!(#default));
+        let into = attrs.into || container.setter_into;
+        let vis = attrs.vis.unwrap_or_else(|| container.vis.clone());
+        // The field's own doc comments, unless replaced by #[builder(doc = "...")].
+        let docs = match attrs.doc {
+            Some(doc) => vec![quote!(#[doc = #doc])],
+            None => field
+                .attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("doc"))
+                .map(|attr| quote!(#attr))
+                .collect(),
+        };
+
+        Ok(BuilderField {
+            name,
+            member,
+            ty,
+            kind,
+            default,
+            into,
+            vis,
+            docs,
+            validate: attrs.validate,
+            default_async: attrs.default_async,
+            secret: attrs.secret.is_some(),
+        })
+    }
+
+    fn is_required(&self) -> bool {
+        matches!(self.kind, FieldKind::Required) && self.default.is_none()
+    }
+
+    // The type of this field in the builder. A collection is kept as an
+    // Option<C> whether or not the field is one, so that telling an untouched
+    // collection apart needs nothing beyond Default and Extend.
+    fn storage_ty(&self) -> TokenStream2 {
+        let ty = self.ty;
+        match &self.kind {
+            FieldKind::Required | FieldKind::Repeated { optional: None, .. } => {
+                quote!(core::option::Option<#ty>)
+            }
+            FieldKind::Optional(_) | FieldKind::Repeated { optional: Some(_), .. } => quote!(#ty),
+            FieldKind::SubBuilder { builder, .. } => quote!(#builder),
+        }
+    }
+
+    // The methods named after this field that set it, each with the key of
+    // #[builder(...)] that renames it. extend_<field> is left out, since it
+    // can only clash with another field's methods.
+    fn setter_names(&self) -> Vec<(&syn::Ident, &'static str)> {
+        let key = match self.member {
+            syn::Member::Named(_) => "rename",
+            syn::Member::Unnamed(_) => "name",
+        };
+        match &self.kind {
+            FieldKind::Repeated { each, .. } if each == &self.name => vec![(each, "each")],
+            FieldKind::Repeated { each, .. } => vec![(each, "each"), (&self.name, key)],
+            _ => vec![(&self.name, key)],
+        }
+    }
+
+    fn is_sub_builder(&self) -> bool {
+        matches!(self.kind, FieldKind::SubBuilder { .. })
+    }
+
+    // What the builder keeps for this field once it has been given `value`.
+    fn store(&self, value: TokenStream2) -> TokenStream2 {
+        match self.kind {
+            FieldKind::Required | FieldKind::Repeated { optional: None, .. } => {
+                quote!(core::option::Option::Some(#value))
+            }
+            FieldKind::Optional(_) | FieldKind::Repeated { optional: Some(_), .. } => value,
+            FieldKind::SubBuilder { .. } => quote!(core::convert::From::from(#value)),
+        }
+    }
+
+    // What merge() keeps for this field out of `self` and `other`.
+    fn merge_value(&self) -> TokenStream2 {
+        let name = &self.name;
+        match self.kind {
+            FieldKind::Required
+            | FieldKind::Optional(_)
+            | FieldKind::Repeated { merge: Merge::Replace, .. } => {
+                quote!(core::option::Option::or(other.#name, self.#name))
+            }
+            FieldKind::Repeated { merge: Merge::Append, .. } => quote! {
+                match (self.#name, other.#name) {
+                    (core::option::Option::Some(mut items), core::option::Option::Some(other)) => {
+                        core::iter::Extend::extend(&mut items, other);
+                        core::option::Option::Some(items)
+                    }
+                    (items, other) => core::option::Option::or(other, items),
+                }
+            },
+            FieldKind::SubBuilder { .. } => quote!(self.#name.merge(other.#name)),
+        }
+    }
+
+    // The value of this field in the built struct: moved out of the builder
+    // when build() takes it by value, cloned out of it otherwise.
+    fn build_value(&self, by_value: bool) -> TokenStream2 {
+        let name = &self.name;
+        let field = if by_value { quote!(self.#name) } else { quote!(&self.#name) };
+        let take = |value: TokenStream2| {
+            if by_value { value } else { quote!(core::clone::Clone::clone(#value)) }
+        };
+
+        match (&self.kind, &self.default) {
+            (FieldKind::Required, None) => {
+                let value = take(field);
+                quote!(#value.unwrap())
+            }
+            (FieldKind::Required, Some(default)) => {
+                let value = take(quote!(value));
+                quote! {
+                    match #field {
+                        core::option::Option::Some(value) => #value,
+                        core::option::Option::None => #default,
+                    }
+                }
+            }
+            (FieldKind::Optional(_), Some(default)) => {
+                let value = take(quote!(value));
+                quote! {
+                    match #field {
+                        core::option::Option::Some(value) => core::option::Option::Some(#value),
+                        core::option::Option::None => #default,
+                    }
+                }
+            }
+            (FieldKind::Repeated { optional: None, .. }, Some(default)) => {
+                let value = take(quote!(value));
+                quote! {
+                    match #field {
+                        core::option::Option::Some(value) => #value,
+                        core::option::Option::None => #default,
+                    }
+                }
+            }
+            (FieldKind::Repeated { optional: None, .. }, None) => {
+                let value = take(field);
+                quote!(core::option::Option::unwrap_or_default(#value))
+            }
+            (FieldKind::Repeated { optional: Some(_), .. }, Some(default)) => {
+                let value = take(field);
+                quote! {
+                    if self.#name.is_none() { #default } else { #value }
+                }
+            }
+            (FieldKind::Optional(_) | FieldKind::Repeated { .. }, None) => take(field),
+            // Built by build() before the value is put together.
+            (FieldKind::SubBuilder { .. }, _) => {
+                let built = self.sub_builder_value();
+                quote!(#built.unwrap())
+            }
+        }
+    }
+
+    // The locals of build_async() holding the pinned initializer of this field
+    // and, once it has finished, its output.
+    fn initializer_locals(&self) -> (syn::Ident, syn::Ident) {
+        let name = self.display_name();
+        let span = self.name.span();
+        (
+            format_ident!("__{}_init", name, span = span),
+            format_ident!("__{}_output", name, span = span),
+        )
+    }
+
+    // The local variable holding the value built by a sub-builder.
+    fn sub_builder_value(&self) -> syn::Ident {
+        format_ident!("__{}_built", self.display_name(), span = self.name.span())
+    }
+
+    // The field name as the user wrote it, without any `r#` prefix.
+    fn display_name(&self) -> String {
+        self.name.unraw().to_string()
+    }
+
+    // extend_items for a field named items, and extend_0 rather than
+    // extend__0 for a tuple field left with its positional name _0.
+    fn extend_method(&self) -> syn::Ident {
+        let field_name = match &self.member {
+            syn::Member::Unnamed(index) if self.name == format!("_{}", index.index) => {
+                index.index.to_string()
+            }
+            _ => self.display_name(),
+        };
+        format_ident!("extend_{}", field_name, span = self.name.span())
+    }
+
+    // A method of a sub-builder, spanned at the field type so that a type
+    // whose builder lacks it is reported there.
+    fn nested_method(&self, name: &str) -> syn::Ident {
+        syn::Ident::new(name, self.ty.span())
+    }
+
+    // MissingCurrentDir for a field named current_dir.
+    fn missing_variant(&self) -> syn::Ident {
+        format_ident!("Missing{}", self.pascal_name(), span = self.name.span())
+    }
+
+    // NestedTls for a sub-builder field named tls.
+    fn nested_variant(&self) -> syn::Ident {
+        format_ident!("Nested{}", self.pascal_name(), span = self.name.span())
+    }
+
+    // CurrentDir for a field named current_dir.
+    fn pascal_name(&self) -> String {
+        self.display_name()
+            .split('_')
+            .map(|word| {
+                let mut chars = word.chars();
+                match chars.next() {
+                    Some(first) => first.to_uppercase().chain(chars).collect(),
+                    None => String::new(),
+                }
+            })
+            .collect()
+    }
+
+    fn setters(&self, pattern: Pattern) -> TokenStream2 {
+        let name = &self.name;
+        let ty = self.ty;
+        let head = self.setter_head();
+
+        match &self.kind {
+            FieldKind::Required => {
+                let (generics, arg, value) = self.setter_arg(name, ty);
+                pattern.setter(
+                    head.clone(),
+                    name,
+                    generics,
+                    quote!(#name: #arg),
+                    quote! {
+                        __builder.#name = core::option::Option::Some(#value);
+                    },
+                )
+            }
+            FieldKind::Optional(inner) => {
+                let (generics, arg, value) = self.setter_arg(name, inner);
+                pattern.setter(
+                    head.clone(),
+                    name,
+                    generics,
+                    quote!(#name: #arg),
+                    quote! {
+                        __builder.#name = core::option::Option::Some(#value);
+                    },
+                )
+            }
+            // The sub-builder is changed in place, whatever the pattern of
+            // this builder.
+            FieldKind::SubBuilder { builder, .. } => quote! {
+                #head fn #name(&mut self) -> &mut #builder {
+                    &mut self.#name
+                }
+            },
+            FieldKind::Repeated { each, items, optional, .. } => {
+                // The collection that items are added to, created by the first
+                // one.
+                let collection = quote! {
+                    __builder.#name.get_or_insert_with(core::default::Default::default)
+                };
+                let (generics, args, item) = match items {
+                    Items::Single(item) => {
+                        let (generics, arg, value) = self.setter_arg(each, item);
+                        (generics, quote!(#each: #arg), value)
+                    }
+                    // With setter(into), the key and the value convert
+                    // separately.
+                    Items::Map(key, value) if self.into => (
+                        Some(quote! {
+                            <
+                                __Key: core::convert::Into<#key>,
+                                __Value: core::convert::Into<#value>,
+                            >
+                        }),
+                        quote!(key: __Key, value: __Value),
+                        quote!((core::convert::Into::into(key), core::convert::Into::into(value))),
+                    ),
+                    Items::Map(key, value) => {
+                        (None, quote!(key: #key, value: #value), quote!((key, value)))
+                    }
+                };
+                let each_setter = pattern.setter(
+                    head.clone(),
+                    each,
+                    generics,
+                    args,
+                    quote! {
+                        core::iter::Extend::extend(#collection, core::iter::once(#item));
+                    },
+                );
+
+                // extend__envs for a field _envs is not snake case, but is
+                // still the name of that field's method.
+                let extend = self.extend_method();
+                let allow =
+                    extend.to_string().contains("__").then(|| quote!(#[allow(non_snake_case)]));
+                let item_ty = items.item();
+                let extend_setter = pattern.setter(
+                    quote!(#allow #head),
+                    &extend,
+                    Some(quote!(<__Items: core::iter::IntoIterator<Item = #item_ty>>)),
+                    quote!(items: __Items),
+                    quote! {
+                        core::iter::Extend::extend(#collection, items);
+                    },
+                );
+
+                // If the one-at-a-time method has the same name as the field,
+                // the all-at-once method would conflict with it.
+                if each == name {
+                    quote! {
+                        #each_setter
+                        #extend_setter
+                    }
+                } else {
+                    let (generics, arg, value) = self.setter_arg(name, optional.unwrap_or(ty));
+                    let setter = pattern.setter(
+                        head.clone(),
+                        name,
+                        generics,
+                        quote!(#name: #arg),
+                        quote! {
+                            __builder.#name = core::option::Option::Some(#value);
+                        },
+                    );
+                    quote! {
+                        #each_setter
+                        #extend_setter
+                        #setter
+                    }
+                }
+            }
+        }
+    }
+
+    // `value` checked by the field's own validation function, if it has one.
+    // A rejected value is reported with the name of the field.
+    fn validated(&self, value: TokenStream2, target: &Target) -> TokenStream2 {
+        let Some(validate) = &self.validate else {
+            return value;
+        };
+        let error_name = &target.error_name;
+        let alloc = target.alloc();
+        let field_name = self.display_name();
+        quote! {{
+            let field = #value;
+            if let core::result::Result::Err(message) = #validate(&field) {
+                let message = #alloc::format!("{}: {}", #field_name, message);
+                let error = #error_name::Validation(message);
+                return core::result::Result::Err(core::convert::From::from(error));
+            }
+            field
+        }}
+    }
+
+    // What goes before `fn` in every setter of this field.
+    fn setter_head(&self) -> TokenStream2 {
+        let vis = &self.vis;
+        let docs = &self.docs;
+        quote!(#(#docs)* #vis)
+    }
+
+    // The generics, argument type and stored value of a setter whose argument
+    // `arg` is of type `ty`. With setter(into) the setter accepts anything that
+    // converts into `ty`, through a type parameter named so that it cannot be
+    // one of the input's own:
+    //
+    //     pub fn executable<__Value: Into<String>>(&mut self, executable: __Value)
+    fn setter_arg(
+        &self,
+        arg: &syn::Ident,
+        ty: &syn::Type,
+    ) -> (Option<TokenStream2>, TokenStream2, TokenStream2) {
+        if self.into {
+            (
+                Some(quote!(<__Value: core::convert::Into<#ty>>)),
+                quote!(__Value),
+                quote!(core::convert::Into::into(#arg)),
+            )
+        } else {
+            (None, quote!(#ty), quote!(#arg))
+        }
+    }
+}
+
+// HttpGet -> http_get, for naming the builder constructor of each variant.
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            // Start a new word after a lowercase letter or digit, and before the
+            // last capital of an acronym (HTTPGet -> http_get).
+            let after_lower =
+                i > 0 && (chars[i - 1].is_lowercase() || chars[i - 1].is_ascii_digit());
+            let ends_acronym = i > 0
+                && chars[i - 1].is_uppercase()
+                && chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if after_lower || ends_acronym {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+
+    snake
+}
+
+// TlsConfigBuilder and TlsConfigBuilderError for a field of type TlsConfig,
+// named the way this macro names them. The builder keeps the field type's
+// generic arguments; the error type has none.
+fn sub_builder_types(ty: &syn::Type) -> Result<(Box<syn::Type>, syn::Path), syn::Error> {
+    let path = match ty {
+        syn::Type::Path(type_path) if type_path.qself.is_none() => &type_path.path,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                ty,
+                "#[builder(sub_builder)] requires a field whose type derives Builder",
+            ));
+        }
+    };
+
+    let mut builder = path.clone();
+    let mut error = path.clone();
+    let segment = builder.segments.last_mut().unwrap();
+    segment.ident = format_ident!("{}Builder", segment.ident);
+    let segment = error.segments.last_mut().unwrap();
+    segment.ident = format_ident!("{}BuilderError", segment.ident);
+    segment.arguments = syn::PathArguments::None;
+
+    Ok((Box::new(syn::Type::Path(syn::TypePath { qself: None, path: builder })), error))
+}
+
+// The items of a collection field with #[builder(each = "...")], judged by
+// the last segment of its type like generic_argument, so that std::vec::Vec<T>
+// and alloc::vec::Vec<T> are recognized as well as Vec<T>.
+fn collection_items(ty: &syn::Type) -> Items {
+    let unknown =
+        || Items::Single(Box::new(syn::parse_quote!(<#ty as core::iter::IntoIterator>::Item)));
+
+    let syn::Type::Path(type_path) = ty else {
+        return unknown();
+    };
+    if type_path.qself.is_some() {
+        return unknown();
+    }
+
+    let Some(segment) = type_path.path.segments.last() else {
+        return unknown();
+    };
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return unknown();
+    };
+    let mut types = args.args.iter().filter_map(|arg| match arg {
+        syn::GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    });
+
+    match segment.ident.to_string().as_str() {
+        "HashMap" | "BTreeMap" => match (types.next(), types.next()) {
+            (Some(key), Some(value)) => Items::Map(Box::new(key.clone()), Box::new(value.clone())),
+            _ => unknown(),
+        },
+        "Vec" | "VecDeque" | "LinkedList" | "BinaryHeap" | "HashSet" | "BTreeSet" => {
+            match types.next() {
+                Some(item) => Items::Single(Box::new(item.clone())),
+                None => unknown(),
+            }
+        }
+        // SmallVec<[T; N]> keeps its items inline in an array.
+        "SmallVec" => match types.next() {
+            Some(syn::Type::Array(array)) => Items::Single(array.elem.clone()),
+            _ => unknown(),
+        },
+        _ => unknown(),
+    }
+}
+
+// If `ty` is written as `Wrapper<T>` (possibly with a leading path such as
+// `std::option::Option<T>`), return T.
+fn generic_argument<'a>(ty: &'a syn::Type, wrapper: &str) -> Option<&'a syn::Type> {
+    let syn::Type::Path(type_path) = ty else {
+        return None;
+    };
+    if type_path.qself.is_some() {
+        return None;
+    }
+
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
     };
+    if args.args.len() != 1 {
+        return None;
+    }
 
-    TokenStream::from(expanded)
+    match args.args.first()? {
+        syn::GenericArgument::Type(inner) => Some(inner),
+        _ => None,
+    }
 }
@@ -0,0 +1,216 @@
+// #[builder(typestate)]: the builder carries one type parameter per required
+// field, which is `()` while the field is unset and `(T,)` once it has been
+// given a value. build() is only implemented once every parameter is `(T,)`,
+// so forgetting a required field is a type error rather than an Err at runtime.
+//
+//     pub struct CommandBuilder<__Executable> {
+//         executable: __Executable,
+//         current_dir: Option<String>,
+//     }
+//
+//     impl CommandBuilder<(String,)> {
+//         pub fn build(self) -> Command { ... }
+//     }
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+
+use crate::{FieldKind, Pattern, Target, error_enum};
+
+pub(crate) fn expand(target: &Target) -> TokenStream2 {
+    let Target { type_name, builder_name, constructor, path, generics, fields, .. } = target;
+    let vis = &target.attrs.vis;
+
+    // The type parameter tracking each required field, in field order.
+    let params: Vec<Option<syn::Ident>> = fields
+        .iter()
+        .map(|f| f.is_required().then(|| format_ident!("__{}", f.pascal_name())))
+        .collect();
+    let all_params: Vec<&syn::Ident> = params.iter().flatten().collect();
+
+    // The builder has the generic parameters of the input type followed by the
+    // state parameters. Arguments for the input's own parameters are spliced
+    // in front of the state arguments wherever the builder type is named.
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let mut builder_generics = (*generics).clone();
+    builder_generics
+        .params
+        .extend(all_params.iter().map(|param| -> syn::GenericParam { syn::parse_quote!(#param) }));
+    let (builder_impl_generics, _, _) = builder_generics.split_for_impl();
+    let input_args = generic_args(generics);
+    let marker_field = target.marker_field();
+    let marker_init = target.marker_init();
+
+    // A required field with a default can be left unset, and is stored like
+    // any other field.
+    let builder_fields = fields.iter().zip(&params).map(|(f, param)| {
+        let name = &f.name;
+        match param {
+            Some(param) => quote! { #name: #param },
+            None => {
+                let storage = f.storage_ty();
+                quote! { #name: #storage }
+            }
+        }
+    });
+
+    let builder_inits = fields.iter().map(|f| {
+        let name = &f.name;
+        match f.kind {
+            FieldKind::Required if f.is_required() => quote! { #name: () },
+            FieldKind::Required | FieldKind::Optional(_) | FieldKind::Repeated { .. } => {
+                quote! { #name: core::option::Option::None }
+            }
+            // Rejected together with typestate when the field is parsed.
+            FieldKind::SubBuilder { .. } => unreachable!(),
+        }
+    });
+    let unset = all_params.iter().map(|_| quote!(()));
+
+    let setters = fields.iter().map(|f| {
+        let name = &f.name;
+        let ty = f.ty;
+        // Setting a required field moves every other field into a builder
+        // whose type records that this one is now present. Every other setter
+        // is the same as for an owned builder.
+        if f.is_required() {
+            let head = f.setter_head();
+            let (generics, arg, value) = f.setter_arg(name, ty);
+            let output_params = fields.iter().zip(&params).filter_map(|(other, param)| {
+                let param = param.as_ref()?;
+                Some(if &other.name == name { quote!((#ty,)) } else { quote!(#param) })
+            });
+            let moved_fields = fields.iter().map(|other| {
+                let other_name = &other.name;
+                if other_name == name {
+                    quote! { #other_name: (#value,) }
+                } else {
+                    quote! { #other_name: self.#other_name }
+                }
+            });
+            let moved_marker = marker_field.as_ref().map(|_| {
+                quote! { __marker: self.__marker, }
+            });
+            quote! {
+                #head fn #name #generics(
+                    self,
+                    #name: #arg,
+                ) -> #builder_name<#(#input_args,)* #(#output_params),*> {
+                    #builder_name {
+                        #(#moved_fields,)*
+                        #moved_marker
+                    }
+                }
+            }
+        } else {
+            f.setters(Pattern::Owned)
+        }
+    });
+
+    let complete = fields.iter().filter(|f| f.is_required()).map(|f| {
+        let ty = f.ty;
+        quote!((#ty,))
+    });
+
+    let build_fields = fields.iter().map(|f| {
+        let name = &f.name;
+        let member = &f.member;
+        let value = if f.is_required() { quote!(self.#name.0) } else { f.build_value(true) };
+        let value = f.validated(value, target);
+        quote! { #member: #value }
+    });
+    let skipped_fields = target.skipped_fields();
+
+    let container_default = target.container_default();
+    let default_bounds = target.container_default_bounds();
+
+    // Converting an existing value gives a builder with every field set.
+    let complete_ty = {
+        let complete = complete.clone();
+        quote!(#builder_name<#(#input_args,)* #(#complete),*>)
+    };
+    let conversions = target.conversions(complete_ty, |f, value| {
+        if f.is_required() { quote!((#value,)) } else { f.store(value) }
+    });
+
+    // Missing fields cannot get this far, so build() only returns a Result,
+    // with an error type of just the Validation variant, when there is a
+    // validation function to run.
+    let validation = target.validation();
+    let build_error = target.build_error();
+    let (output, value, error) = if target.validates() {
+        (
+            quote!(core::result::Result<#type_name #ty_generics, #build_error>),
+            quote! {
+                #validation
+                core::result::Result::Ok(value)
+            },
+            Some(error_enum(target, &[])),
+        )
+    } else {
+        (quote!(#type_name #ty_generics), quote!(value), None)
+    };
+
+    quote! {
+        #vis struct #builder_name #builder_impl_generics #where_clause {
+            #(#builder_fields,)*
+            #marker_field
+        }
+
+        impl #impl_generics #type_name #ty_generics #where_clause {
+            #vis fn #constructor() -> #builder_name<#(#input_args,)* #(#unset),*> {
+                #builder_name {
+                    #(#builder_inits,)*
+                    #marker_init
+                }
+            }
+        }
+
+        impl #builder_impl_generics #builder_name<#(#input_args,)* #(#all_params),*>
+        #where_clause
+        {
+            #(#setters)*
+        }
+
+        impl #impl_generics #builder_name<#(#input_args,)* #(#complete),*> #where_clause {
+            #vis fn build(self) -> #output
+            where
+                #(#default_bounds,)*
+            {
+                #container_default
+                let value = #path {
+                    #(#build_fields,)*
+                    #(#skipped_fields,)*
+                };
+                #value
+            }
+        }
+
+        #conversions
+
+        #error
+    }
+}
+
+// The arguments naming each parameter of `generics`: `'a, T, N` for
+// `<'a, T: Codec, const N: usize>`.
+fn generic_args(generics: &syn::Generics) -> Vec<TokenStream2> {
+    generics
+        .params
+        .iter()
+        .map(|param| match param {
+            syn::GenericParam::Lifetime(param) => {
+                let lifetime = &param.lifetime;
+                quote!(#lifetime)
+            }
+            syn::GenericParam::Type(param) => {
+                let ident = &param.ident;
+                quote!(#ident)
+            }
+            syn::GenericParam::Const(param) => {
+                let ident = &param.ident;
+                quote!(#ident)
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,27 @@
+// When build() is called before every required field has been set, the error
+// should name all of the missing fields rather than only the first one, so
+// that callers can report a complete list back to their users.
+//
+// Optional fields and repeated fields never count as missing.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    env: Vec<String>,
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let err = Command::builder().build().err().unwrap();
+    assert_eq!(err.to_string(), "missing fields: executable, env");
+
+    let err = Command::builder().executable("cargo".to_owned()).build().err().unwrap();
+    assert_eq!(err.to_string(), "missing field: env");
+
+    let command = Command::builder().executable("cargo".to_owned()).env(vec![]).build().unwrap();
+    assert!(command.args.is_empty());
+}
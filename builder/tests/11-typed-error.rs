@@ -0,0 +1,41 @@
+// The error returned by build() is a generated enum named after the builder,
+// with one Missing<Field> variant per required field. Callers can match on it
+// to handle each missing field differently, and it implements
+// std::error::Error and Display like any other error type.
+//
+//     pub enum CommandBuilderError {
+//         MissingExecutable,
+//         MissingCurrentDir,
+//         Validation(String),
+//         Multiple(Vec<CommandBuilderError>),
+//     }
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    args: Vec<String>,
+    current_dir: String,
+}
+
+fn main() {
+    let err = Command::builder().executable("cargo".to_owned()).args(vec![]).build().err().unwrap();
+    assert_eq!(err, CommandBuilderError::MissingCurrentDir);
+    assert_eq!(err.to_string(), "missing field: current_dir");
+
+    let err = Command::builder().args(vec![]).build().err().unwrap();
+    match &err {
+        CommandBuilderError::Multiple(errors) => assert_eq!(
+            errors,
+            &[CommandBuilderError::MissingExecutable, CommandBuilderError::MissingCurrentDir],
+        ),
+        _ => panic!("expected two missing fields"),
+    }
+    assert_eq!(err.to_string(), "missing fields: executable, current_dir");
+
+    let err = CommandBuilderError::Validation("port must not be 0".to_owned());
+    assert_eq!(err.to_string(), "validation failed: port must not be 0");
+
+    let _: &dyn std::error::Error = &err;
+}
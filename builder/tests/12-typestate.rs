@@ -0,0 +1,41 @@
+// With #[builder(typestate)] on the struct, the builder's type records which
+// required fields have been set. Setters take the builder by value and return
+// a builder of a new type, and build() only exists once every required field
+// is present, so it returns the struct directly instead of a Result.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+    timeout: u64,
+}
+
+fn main() {
+    // Required fields may be given in any order.
+    let command = Command::builder()
+        .timeout(30)
+        .arg("build".to_owned())
+        .executable("cargo".to_owned())
+        .arg("--release".to_owned())
+        .build();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert!(command.current_dir.is_none());
+    assert_eq!(command.timeout, 30);
+
+    // Setting a field again replaces the earlier value.
+    let command = Command::builder()
+        .executable("rustc".to_owned())
+        .current_dir("..".to_owned())
+        .executable("cargo".to_owned())
+        .timeout(1)
+        .build();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+}
@@ -0,0 +1,16 @@
+// In typestate mode, calling build() before every required field has been set
+// is rejected by the compiler.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+    current_dir: Option<String>,
+    timeout: u64,
+}
+
+fn main() {
+    let _ = Command::builder().timeout(30).build();
+}
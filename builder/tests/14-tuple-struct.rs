@@ -0,0 +1,31 @@
+// Tuple structs get positional setters named `_0`, `_1`, ... unless a field
+// gives its setter a name with #[builder(name = "...")]. The field attributes
+// that work on named fields work on tuple fields too, so a collection
+// field with #[builder(each = "...")] gets extend_0 to add several items.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Rgb(u8, #[builder(name = "green")] u8, u8);
+
+#[derive(Builder)]
+pub struct Args(#[builder(each = "arg")] Vec<String>, Option<String>);
+
+fn main() {
+    let color = Rgb::builder()._0(255).green(128)._2(0).build().unwrap();
+    assert_eq!((color.0, color.1, color.2), (255, 128, 0));
+
+    let err = Rgb::builder().green(128).build().err().unwrap();
+    assert_eq!(err.to_string(), "missing fields: _0, _2");
+    assert!(matches!(err, RgbBuilderError::Multiple(_)));
+
+    let err = Rgb::builder()._0(255)._2(0).build().err().unwrap();
+    assert_eq!(err, RgbBuilderError::MissingGreen);
+
+    let args = Args::builder().arg("build".to_owned()).arg("--release".to_owned()).build().unwrap();
+    assert_eq!(args.0, vec!["build", "--release"]);
+    assert!(args.1.is_none());
+
+    let args = Args::builder().extend_0(vec!["test".to_owned()]).arg("--all".to_owned()).build();
+    assert_eq!(args.unwrap().0, vec!["test", "--all"]);
+}
@@ -0,0 +1,52 @@
+// Deriving Builder on an enum generates one builder per variant, each created
+// by a snake_case constructor on the enum and building the enum itself:
+//
+//     impl Shape {
+//         pub fn circle_builder() -> ShapeCircleBuilder { ... }
+//         pub fn rectangle_builder() -> ShapeRectangleBuilder { ... }
+//     }
+//
+//     impl ShapeCircleBuilder {
+//         pub fn build(&self) -> Result<Shape, ShapeCircleBuilderError> { ... }
+//     }
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub enum Shape {
+    Circle {
+        radius: f64,
+        label: Option<String>,
+    },
+    Rectangle(f64, f64),
+    Polygon {
+        #[builder(each = "point")]
+        points: Vec<(f64, f64)>,
+    },
+    Empty,
+}
+
+#[derive(Builder)]
+pub enum Request {
+    HTTPGet { url: String },
+    PostJson2 { url: String },
+}
+
+fn main() {
+    let circle = Shape::circle_builder().radius(1.5).build().unwrap();
+    assert_eq!(circle, Shape::Circle { radius: 1.5, label: None });
+
+    let err = Shape::circle_builder().label("unit".to_owned()).build().err().unwrap();
+    assert_eq!(err, ShapeCircleBuilderError::MissingRadius);
+
+    let rectangle = Shape::rectangle_builder()._0(2.0)._1(3.0).build().unwrap();
+    assert_eq!(rectangle, Shape::Rectangle(2.0, 3.0));
+
+    let polygon = Shape::polygon_builder().point((0.0, 0.0)).point((1.0, 0.0)).build().unwrap();
+    assert_eq!(polygon, Shape::Polygon { points: vec![(0.0, 0.0), (1.0, 0.0)] });
+
+    assert_eq!(Shape::empty_builder().build().unwrap(), Shape::Empty);
+
+    let _ = Request::http_get_builder();
+    let _ = Request::post_json2_builder();
+}
@@ -0,0 +1,12 @@
+// Unions have no safe way to be built field by field, so deriving Builder on
+// one is reported as an error on the `union` keyword.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub union Bits {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
@@ -0,0 +1,87 @@
+// The builder carries over the lifetimes, type and const parameters and the
+// where-clause of the input type:
+//
+//     pub struct RequestBuilder<'a, C: Codec, const N: usize>
+//     where
+//         C: Clone,
+//     {
+//         ...
+//     }
+//
+// Its variance in each parameter is the same as that of the built type, so a
+// builder of borrowed data can be shortened to a smaller lifetime just like the
+// value it builds.
+
+use derive_builder::Builder;
+
+pub trait Codec {
+    fn encode(&self, input: &[u8]) -> Vec<u8>;
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Identity;
+
+impl Codec for Identity {
+    fn encode(&self, input: &[u8]) -> Vec<u8> {
+        input.to_vec()
+    }
+}
+
+#[derive(Builder)]
+pub struct Request<'a, C: Codec, const N: usize>
+where
+    C: Clone,
+{
+    codec: C,
+    body: &'a [u8],
+    headers: [&'a str; N],
+    #[builder(each = "tag")]
+    tags: Vec<&'a str>,
+    timeout: Option<u64>,
+}
+
+// Variant builders only mention some of the enum's parameters.
+#[derive(Builder, Debug, PartialEq)]
+pub enum Either<L, R> {
+    Left(L),
+    Right { value: R },
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Frame<'a, T: Codec + Default> {
+    codec: T,
+    payload: &'a [u8],
+}
+
+fn shorten<'short>(
+    builder: RequestBuilder<'static, Identity, 1>,
+) -> RequestBuilder<'short, Identity, 1> {
+    builder
+}
+
+fn main() {
+    let body = b"hello".to_vec();
+    let request = Request::builder()
+        .codec(Identity)
+        .body(&body)
+        .headers(["accept"])
+        .tag("fast")
+        .build()
+        .unwrap();
+    assert_eq!(request.codec.encode(request.body), b"hello");
+    assert_eq!(request.headers, ["accept"]);
+    assert_eq!(request.tags, vec!["fast"]);
+    assert!(request.timeout.is_none());
+
+    let err = shorten(Request::builder()).codec(Identity).build().err().unwrap();
+    assert_eq!(err.to_string(), "missing fields: body, headers");
+
+    let left = Either::<u8, String>::left_builder()._0(1).build().unwrap();
+    assert_eq!(left, Either::Left(1));
+    let right = Either::<u8, String>::right_builder().value("r".to_owned()).build().unwrap();
+    assert_eq!(right, Either::Right { value: "r".to_owned() });
+
+    let frame = Frame::builder().payload(&body).codec(Identity).build();
+    assert_eq!(frame.codec.encode(frame.payload), b"hello");
+}
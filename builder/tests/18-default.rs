@@ -0,0 +1,85 @@
+// Fields marked #[builder(default)] fall back to Default::default() when they
+// are never set, and #[builder(default = "...")] falls back to the given
+// expression. Such fields are no longer required, so they have no Missing
+// variant in the error type.
+//
+// #[builder(default)] on the struct itself takes every unset field from the
+// struct's own Default impl instead. A field-level default still wins over
+// the struct's.
+
+use std::time::Duration;
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Client {
+    host: String,
+    #[builder(default)]
+    port: u16,
+    #[builder(default = "Duration::from_secs(30)")]
+    timeout: Duration,
+    #[builder(default = "Some(3)")]
+    retries: Option<u32>,
+    #[builder(each = "header", default = "vec![(\"accept\".to_owned(), \"*/*\".to_owned())]")]
+    headers: Vec<(String, String)>,
+}
+
+#[derive(Builder)]
+#[builder(default)]
+pub struct Limits {
+    max_connections: usize,
+    #[builder(default = "1024")]
+    max_body: usize,
+    user_agent: Option<String>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits { max_connections: 100, max_body: 0, user_agent: Some("builder".to_owned()) }
+    }
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Endpoint {
+    path: String,
+    #[builder(default = "\"GET\".to_owned()")]
+    method: String,
+}
+
+fn main() {
+    let client = Client::builder().host("localhost".to_owned()).build().unwrap();
+    assert_eq!(client.port, 0);
+    assert_eq!(client.timeout, Duration::from_secs(30));
+    assert_eq!(client.retries, Some(3));
+    assert_eq!(client.headers, vec![("accept".to_owned(), "*/*".to_owned())]);
+
+    let client = Client::builder()
+        .host("localhost".to_owned())
+        .port(8080)
+        .timeout(Duration::from_secs(1))
+        .retries(0)
+        .header(("x-id".to_owned(), "1".to_owned()))
+        .build()
+        .unwrap();
+    assert_eq!(client.port, 8080);
+    assert_eq!(client.timeout, Duration::from_secs(1));
+    assert_eq!(client.retries, Some(0));
+    assert_eq!(client.headers.len(), 1);
+
+    let err = Client::builder().build().err().unwrap();
+    assert_eq!(err, ClientBuilderError::MissingHost);
+
+    let limits = Limits::builder().build().unwrap();
+    assert_eq!(limits.max_connections, 100);
+    assert_eq!(limits.max_body, 1024);
+    assert_eq!(limits.user_agent.as_deref(), Some("builder"));
+
+    let limits =
+        Limits::builder().max_connections(1).user_agent("curl".to_owned()).build().unwrap();
+    assert_eq!(limits.max_connections, 1);
+    assert_eq!(limits.user_agent.as_deref(), Some("curl"));
+
+    let endpoint = Endpoint::builder().path("/".to_owned()).build();
+    assert_eq!(endpoint.method, "GET");
+}
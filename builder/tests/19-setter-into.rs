@@ -0,0 +1,72 @@
+// With #[builder(setter(into))] on a field, its setters accept any value that
+// converts into the field type:
+//
+//     pub fn executable<__Value: Into<String>>(&mut self, executable: __Value) -> &mut Self
+//
+// Put on the struct, it applies to every field. On Option<T> fields the setter
+// takes T (or anything that converts into T), which #[builder(setter(strip_option))]
+// spells out explicitly. The setter's type parameter does not get in the way
+// of the struct's own, whatever they are called.
+
+use std::path::PathBuf;
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(setter(into))]
+    executable: String,
+    #[builder(each = "arg", setter(into))]
+    args: Vec<String>,
+    #[builder(setter(into, strip_option))]
+    current_dir: Option<PathBuf>,
+    timeout: Option<u64>,
+}
+
+#[derive(Builder)]
+#[builder(setter(into, strip_option))]
+pub struct Env {
+    key: String,
+    value: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(setter(into))]
+pub struct Labeled<VALUE> {
+    label: String,
+    value: VALUE,
+}
+
+#[derive(Builder)]
+#[builder(typestate, setter(into))]
+pub struct Remote {
+    url: String,
+    name: Option<String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo")
+        .arg("build")
+        .arg(String::from("--release"))
+        .current_dir("..")
+        .timeout(5)
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.current_dir, Some(PathBuf::from("..")));
+    assert_eq!(command.timeout, Some(5));
+
+    let env = Env::builder().key("PATH").value("/bin").build().unwrap();
+    assert_eq!(env.key, "PATH");
+    assert_eq!(env.value.as_deref(), Some("/bin"));
+
+    let labeled = Labeled::builder().label("answer").value(42).build().unwrap();
+    assert_eq!((labeled.label.as_str(), labeled.value), ("answer", 42));
+
+    let remote = Remote::builder().url("https://example.com").name("origin").build();
+    assert_eq!(remote.url, "https://example.com");
+    assert_eq!(remote.name.as_deref(), Some("origin"));
+}
@@ -0,0 +1,11 @@
+// setter(strip_option) only makes sense on a field whose type is Option<T>.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(setter(strip_option))]
+    executable: String,
+}
+
+fn main() {}
@@ -0,0 +1,65 @@
+// #[builder(validate = "path::to::fn")] on the struct names a function that
+// build() calls with the fully assembled value before returning it:
+//
+//     fn validate(value: &Server) -> Result<(), String>
+//
+// A rejection comes back from build() as the Validation variant of the
+// builder's error type. In typestate mode build() then returns a Result too.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(validate = "checks::server")]
+pub struct Server {
+    host: String,
+    port: u16,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+}
+
+mod checks {
+    pub fn server(server: &super::Server) -> Result<(), String> {
+        if server.port == 0 {
+            return Err("port must not be 0".to_owned());
+        }
+        if server.args.is_empty() {
+            return Err(format!("{} needs at least one argument", server.host));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Builder, Debug)]
+#[builder(typestate, validate = "non_empty")]
+pub struct Name {
+    value: String,
+}
+
+fn non_empty(name: &Name) -> Result<(), String> {
+    if name.value.is_empty() { Err("name is empty".to_owned()) } else { Ok(()) }
+}
+
+fn main() {
+    let server = Server::builder()
+        .host("localhost".to_owned())
+        .port(8080)
+        .arg("--verbose".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(server.port, 8080);
+
+    let err = Server::builder().host("localhost".to_owned()).port(0).build().unwrap_err();
+    assert_eq!(err, ServerBuilderError::Validation("port must not be 0".to_owned()));
+    assert_eq!(err.to_string(), "validation failed: port must not be 0");
+
+    let err = Server::builder().host("localhost".to_owned()).port(1).build().unwrap_err();
+    assert_eq!(err.to_string(), "validation failed: localhost needs at least one argument");
+
+    // Missing fields are reported before validation runs.
+    let err = Server::builder().port(0).build().unwrap_err();
+    assert_eq!(err, ServerBuilderError::MissingHost);
+
+    assert_eq!(Name::builder().value("x".to_owned()).build().unwrap().value, "x");
+    let err = Name::builder().value(String::new()).build().unwrap_err();
+    assert_eq!(err, NameBuilderError::Validation("name is empty".to_owned()));
+}
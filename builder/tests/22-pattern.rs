@@ -0,0 +1,60 @@
+// #[builder(pattern = "...")] picks how the builder is passed around:
+//
+//   - "mutable" (the default): setters take and return &mut Self, and
+//     build(&self) clones the fields out of the builder.
+//
+//   - "owned": setters take and return Self, and build(self) moves the fields
+//     out, so fields do not need to be Clone.
+//
+//   - "immutable": setters take &self and return an updated copy, so one
+//     partially configured builder can be the base of several others.
+
+use derive_builder::Builder;
+
+// Deliberately not Clone, like a file handle or a socket.
+pub struct Handle(u32);
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Connection {
+    handle: Handle,
+    #[builder(each = "option")]
+    options: Vec<String>,
+    #[builder(default = "30")]
+    timeout: u64,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "immutable")]
+pub struct Request {
+    method: String,
+    path: String,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "mutable")]
+pub struct Command {
+    executable: String,
+}
+
+fn main() {
+    let connection =
+        Connection::builder().handle(Handle(3)).option("nodelay".to_owned()).build().unwrap();
+    assert_eq!(connection.handle.0, 3);
+    assert_eq!(connection.options, vec!["nodelay"]);
+    assert_eq!(connection.timeout, 30);
+
+    let err = Connection::builder().build().err().unwrap();
+    assert_eq!(err, ConnectionBuilderError::MissingHandle);
+
+    let get = Request::builder().method("GET".to_owned());
+    let index = get.path("/".to_owned()).build().unwrap();
+    let about = get.path("/about".to_owned()).build().unwrap();
+    assert_eq!((index.method.as_str(), index.path.as_str()), ("GET", "/"));
+    assert_eq!((about.method.as_str(), about.path.as_str()), ("GET", "/about"));
+    assert!(get.build().is_err());
+
+    let mut builder = Command::builder();
+    builder.executable("cargo".to_owned());
+    assert_eq!(builder.build().unwrap().executable, "cargo");
+}
@@ -0,0 +1,53 @@
+// An existing value converts back into a builder, either by value through
+// `impl From<Config> for ConfigBuilder` or by cloning its fields with
+// `Config::to_builder(&self)`. Changing one field of a value is then a round
+// trip through the builder, which also works from other modules where the
+// struct's private fields rule out struct update syntax.
+//
+// Typestate builders convert into a builder with every field already set.
+
+mod config {
+    use derive_builder::Builder;
+
+    #[derive(Builder, Clone, Debug, PartialEq)]
+    pub struct Config {
+        host: String,
+        port: u16,
+        #[builder(each = "feature")]
+        features: Vec<String>,
+        proxy: Option<String>,
+    }
+
+    impl Config {
+        pub fn port(&self) -> u16 {
+            self.port
+        }
+    }
+
+    #[derive(Builder, Debug, PartialEq)]
+    #[builder(typestate)]
+    pub struct Point<T>(pub T, pub T);
+}
+
+use config::{Config, ConfigBuilder, Point};
+
+fn main() {
+    let config = Config::builder()
+        .host("localhost".to_owned())
+        .port(80)
+        .feature("gzip".to_owned())
+        .build()
+        .unwrap();
+
+    let changed = config.to_builder().port(8080).build().unwrap();
+    assert_eq!(changed.port(), 8080);
+    assert_eq!(changed.to_builder().port(80).build().unwrap(), config);
+
+    let mut builder = ConfigBuilder::from(config.clone());
+    builder.feature("brotli".to_owned());
+    let extended = builder.build().unwrap();
+    assert_ne!(extended, config);
+
+    let point = Point(1, 2).to_builder()._1(5).build();
+    assert_eq!(point, Point(1, 5));
+}
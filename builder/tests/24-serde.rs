@@ -0,0 +1,53 @@
+// #[builder(serde)] derives serde::Deserialize for the builder. Every field of
+// the builder may be missing from the input, so a partially specified config
+// file deserializes into a partially filled builder. Overrides from the
+// environment or the command line can then be applied with the setters before
+// build() checks that nothing required is missing.
+//
+// The crate using this needs its own dependency on serde with the "derive"
+// feature.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(serde)]
+pub struct Config {
+    host: String,
+    port: u16,
+    #[builder(each = "feature")]
+    features: Vec<String>,
+    proxy: Option<String>,
+    #[builder(default = "30")]
+    timeout: u64,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(serde, pattern = "owned")]
+pub struct Wrapper<T> {
+    inner: T,
+}
+
+fn main() {
+    let mut builder: ConfigBuilder =
+        serde_json::from_str(r#"{ "host": "example.com", "features": ["gzip"] }"#).unwrap();
+    let err = builder.build().unwrap_err();
+    assert_eq!(err, ConfigBuilderError::MissingPort);
+
+    let config = builder.port(8080).feature("brotli".to_owned()).build().unwrap();
+    assert_eq!(config.host, "example.com");
+    assert_eq!(config.port, 8080);
+    assert_eq!(config.features, vec!["gzip", "brotli"]);
+    assert!(config.proxy.is_none());
+    assert_eq!(config.timeout, 30);
+
+    let builder: ConfigBuilder = serde_json::from_str(
+        r#"{ "host": "localhost", "port": 1, "proxy": "socks5://proxy", "timeout": 5 }"#,
+    )
+    .unwrap();
+    let config = builder.build().unwrap();
+    assert_eq!(config.proxy.as_deref(), Some("socks5://proxy"));
+    assert_eq!(config.timeout, 5);
+
+    let builder: WrapperBuilder<Vec<u8>> = serde_json::from_str(r#"{ "inner": [1, 2] }"#).unwrap();
+    assert_eq!(builder.build().unwrap(), Wrapper { inner: vec![1, 2] });
+}
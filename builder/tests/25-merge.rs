@@ -0,0 +1,66 @@
+// Builders can be layered: merge() takes a second builder whose fields, where
+// they were set, win over those of the first. Starting from defaults and
+// merging in a config file, then the environment, then the command line gives
+// each layer precedence over the ones before it.
+//
+// Repeated fields append the items of the later builder by default. With
+// #[builder(merge = "replace")] a later builder that was given any items
+// replaces the earlier ones instead.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Config {
+    host: String,
+    port: u16,
+    proxy: Option<String>,
+    #[builder(each = "feature")]
+    features: Vec<String>,
+    #[builder(each = "include", merge = "replace")]
+    includes: Vec<String>,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(pattern = "owned")]
+pub struct Wrapper<T> {
+    inner: T,
+    label: Option<String>,
+}
+
+fn main() {
+    let mut defaults = Config::builder();
+    defaults.host("localhost".to_owned()).port(80).feature("gzip".to_owned());
+    defaults.include("/etc/app".to_owned());
+
+    let mut file = Config::builder();
+    file.port(8080).feature("tls".to_owned());
+
+    let mut cli = Config::builder();
+    cli.proxy("socks5://proxy".to_owned()).include("./conf".to_owned());
+
+    let config = defaults.merge(file).merge(cli).build().unwrap();
+    assert_eq!(
+        config,
+        Config {
+            host: "localhost".to_owned(),
+            port: 8080,
+            proxy: Some("socks5://proxy".to_owned()),
+            features: vec!["gzip".to_owned(), "tls".to_owned()],
+            includes: vec!["./conf".to_owned()],
+        }
+    );
+
+    // An empty builder changes nothing.
+    let mut only = Config::builder();
+    only.host("example.com".to_owned()).port(443).include("/etc/app".to_owned());
+    let config = only.merge(Config::builder()).build().unwrap();
+    assert_eq!(config.includes, vec!["/etc/app".to_owned()]);
+
+    let wrapper = Wrapper::builder()
+        .inner(1)
+        .label("first".to_owned())
+        .merge(Wrapper::builder().inner(2))
+        .build()
+        .unwrap();
+    assert_eq!(wrapper, Wrapper { inner: 2, label: Some("first".to_owned()) });
+}
@@ -0,0 +1,12 @@
+// A merge strategy only makes sense for a field whose items are collected one
+// by one with #[builder(each = "...")].
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Config {
+    #[builder(merge = "replace")]
+    includes: Vec<String>,
+}
+
+fn main() {}
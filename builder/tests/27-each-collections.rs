@@ -0,0 +1,124 @@
+// #[builder(each = "...")] is not limited to Vec. Any collection that
+// implements Default and Extend<Item> can be filled one item at a time: sets
+// like HashSet and BTreeSet take one element per call, while HashMap and
+// BTreeMap take a key and a value. For a collection the macro doesn't
+// recognize, the item type is taken from its IntoIterator implementation.
+// Nothing more is needed of the collection for #[builder(merge = "replace")]
+// or a default either, which go by whether any items were added.
+//
+// Every such field also gets an extend_<field> method which adds all the
+// items of an iterator at once. The name is kept as written, so a field
+// _args and a field args get extend__args and extend_args; the former is
+// allowed not to be snake case, so that it compiles under
+// #![deny(warnings)]. The type parameters of these methods are named so as
+// not to clash with the struct's own.
+
+#![deny(warnings)]
+
+use derive_builder::Builder;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Request {
+    url: String,
+    #[builder(each = "header", setter(into))]
+    headers: HashMap<String, String>,
+    #[builder(each = "feature")]
+    features: BTreeSet<String>,
+    #[builder(each = "retry")]
+    retries: VecDeque<u32>,
+    #[builder(each = "tag")]
+    tags: Tags,
+    #[builder(each = "label", merge = "replace", default = "Tags(vec![\"new\".to_owned()])")]
+    labels: Tags,
+}
+
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct Tags(Vec<String>);
+
+impl Extend<String> for Tags {
+    fn extend<I: IntoIterator<Item = String>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+impl IntoIterator for Tags {
+    type Item = String;
+    type IntoIter = std::vec::IntoIter<String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(each = "env")]
+    _envs: Vec<String>,
+}
+
+#[derive(Builder)]
+pub struct Table<ITEMS: Clone, KEY: Ord + Clone, VALUE: Clone> {
+    #[builder(each = "item")]
+    items: Vec<ITEMS>,
+    #[builder(each = "entry", setter(into))]
+    entries: std::collections::BTreeMap<KEY, VALUE>,
+}
+
+fn main() {
+    let request = Request::builder()
+        .url("https://example.com".to_owned())
+        .header("Accept", "text/html")
+        .header("Host", "example.com")
+        .feature("tls".to_owned())
+        .feature("gzip".to_owned())
+        .feature("tls".to_owned())
+        .retry(1)
+        .extend_retries(vec![2, 4])
+        .tag("fast".to_owned())
+        .build()
+        .unwrap();
+
+    let mut headers = HashMap::new();
+    headers.insert("Accept".to_owned(), "text/html".to_owned());
+    headers.insert("Host".to_owned(), "example.com".to_owned());
+    assert_eq!(request.headers, headers);
+    assert_eq!(request.features.into_iter().collect::<Vec<_>>(), ["gzip", "tls"]);
+    assert_eq!(request.retries, [1, 2, 4]);
+    assert_eq!(request.tags, Tags(vec!["fast".to_owned()]));
+    assert_eq!(request.labels, Tags(vec!["new".to_owned()]));
+
+    let draft = || {
+        let mut builder = Request::builder();
+        builder.url("https://example.com".to_owned()).label("draft".to_owned());
+        builder
+    };
+    let mut overrides = Request::builder();
+    overrides.label("final".to_owned());
+    let request = draft().merge(overrides).build().unwrap();
+    assert_eq!(request.labels, Tags(vec!["final".to_owned()]));
+    let request = draft().merge(Request::builder()).build().unwrap();
+    assert_eq!(request.labels, Tags(vec!["draft".to_owned()]));
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .extend_args(vec!["--release".to_owned()])
+        .extend__envs(vec!["RUST_LOG=debug".to_owned()])
+        .build()
+        .unwrap();
+    assert_eq!(command.args, ["build", "--release"]);
+    assert_eq!(command._envs, ["RUST_LOG=debug"]);
+
+    let table = Table::<u8, String, String>::builder()
+        .item(1)
+        .extend_items([2, 3])
+        .entry("a", "b")
+        .build()
+        .unwrap();
+    assert_eq!(table.items, [1, 2, 3]);
+    assert_eq!(table.entries["a"], "b");
+}
@@ -0,0 +1,80 @@
+// A field whose type derives Builder itself can be marked
+// #[builder(sub_builder)]. The outer builder then holds a builder for that
+// field, reached through an accessor instead of a setter, and build() builds
+// it along with everything else:
+//
+//     impl ServerBuilder {
+//         pub fn tls(&mut self) -> &mut TlsConfigBuilder { ... }
+//     }
+//
+// When the inner builder fails, the outer error wraps it in a Nested<Field>
+// variant, and the missing fields are reported with their full path, such as
+// `tls.cert_path`.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Server {
+    host: String,
+    #[builder(sub_builder)]
+    tls: TlsConfig,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct TlsConfig {
+    cert_path: String,
+    #[builder(sub_builder)]
+    ciphers: Ciphers,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(validate = "Ciphers::check")]
+pub struct Ciphers {
+    #[builder(each = "suite")]
+    suites: Vec<String>,
+}
+
+impl Ciphers {
+    fn check(&self) -> Result<(), String> {
+        if self.suites.is_empty() {
+            return Err("no cipher suites".to_owned());
+        }
+        Ok(())
+    }
+}
+
+fn main() {
+    let mut builder = Server::builder();
+    builder.host("example.com".to_owned());
+    builder.tls().cert_path("/etc/cert.pem".to_owned());
+    builder.tls().ciphers().suite("TLS_AES_128_GCM_SHA256".to_owned());
+    let server = builder.build().unwrap();
+    assert_eq!(server.tls.cert_path, "/etc/cert.pem");
+    assert_eq!(server.tls.ciphers.suites, ["TLS_AES_128_GCM_SHA256"]);
+
+    let mut builder = Server::builder();
+    builder.host("example.com".to_owned());
+    builder.tls().ciphers().suite("TLS_AES_128_GCM_SHA256".to_owned());
+    let err = builder.build().unwrap_err();
+    assert_eq!(err, ServerBuilderError::NestedTls(TlsConfigBuilderError::MissingCertPath));
+    assert_eq!(err.missing_fields(), ["tls.cert_path"]);
+    assert_eq!(err.to_string(), "missing field: tls.cert_path");
+
+    let err = Server::builder().build().unwrap_err();
+    assert_eq!(err.missing_fields(), ["host", "tls.cert_path"]);
+    assert_eq!(err.to_string(), "missing fields: host, tls.cert_path");
+
+    let mut builder = Server::builder();
+    builder.host("example.com".to_owned());
+    builder.tls().cert_path("/etc/cert.pem".to_owned());
+    let err = builder.build().unwrap_err();
+    assert_eq!(err.to_string(), "tls: ciphers: validation failed: no cipher suites");
+
+    let mut builder = Server::builder();
+    builder.tls().cert_path("/etc/cert.pem".to_owned());
+    let err = builder.build().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "missing field: host; tls: ciphers: validation failed: no cipher suites",
+    );
+}
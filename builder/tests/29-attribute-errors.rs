@@ -0,0 +1,23 @@
+// Every mistake in the #[builder(...)] attributes is reported, each at the key
+// or value it is about, rather than only the first one found. Misspelled keys
+// come with a suggestion of the closest key, and a key given twice, keys that
+// cannot be combined, and values of the wrong form each get their own error.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(patern = "owned", serde = true)]
+pub struct Command {
+    #[builder(defualt)]
+    executable: String,
+    #[builder(each = "arg", each = "argument")]
+    args: Vec<String>,
+    #[builder(each = "env", skip)]
+    env: Vec<String>,
+    #[builder(rename = current_directory, setter(into, intoo))]
+    current_dir: Option<String>,
+    #[builder(frobnicate)]
+    verbose: bool,
+}
+
+fn main() {}
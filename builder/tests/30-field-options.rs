@@ -0,0 +1,54 @@
+// Besides each, default and setter(...), a field can be configured with:
+//
+//   - skip: the field gets no setter and is always built from its default,
+//     #[builder(default = "...")] if given and Default::default() otherwise.
+//   - rename = "...": the name of the setter, when it should differ from the
+//     name of the field.
+//   - vis = "...": the visibility of the setter instead of `pub`.
+//   - doc = "...": documentation for the setter.
+//   - validate = "...": a fn(&T) -> Result<(), String> that build() runs on the
+//     value of the field. Its error is reported with the name of the field.
+
+mod command {
+    use derive_builder::Builder;
+
+    #[derive(Builder, Debug)]
+    pub struct Command {
+        pub executable: String,
+        #[builder(rename = "arg_list")]
+        pub args: Vec<String>,
+        #[builder(skip)]
+        pub history: Vec<String>,
+        #[builder(skip, default = "3")]
+        pub retries: u32,
+        #[builder(vis = "pub(crate)", doc = "Timeout in seconds.")]
+        pub timeout: Option<u64>,
+        #[builder(validate = "check_nice")]
+        pub nice: i32,
+    }
+
+    fn check_nice(nice: &i32) -> Result<(), String> {
+        if (-20..20).contains(nice) { Ok(()) } else { Err(format!("{} is out of range", nice)) }
+    }
+
+    pub fn with_timeout(builder: &mut CommandBuilder) {
+        builder.timeout(5);
+    }
+}
+
+use command::{Command, CommandBuilderError};
+
+fn main() {
+    let mut builder = Command::builder();
+    builder.executable("cargo".to_owned()).arg_list(vec!["build".to_owned()]).nice(5);
+    command::with_timeout(&mut builder);
+    let command = builder.build().unwrap();
+    assert_eq!(command.args, ["build"]);
+    assert!(command.history.is_empty());
+    assert_eq!(command.retries, 3);
+    assert_eq!(command.timeout, Some(5));
+
+    let err = builder.nice(40).build().unwrap_err();
+    assert_eq!(err, CommandBuilderError::Validation("nice: 40 is out of range".to_owned()));
+    assert_eq!(err.to_string(), "validation failed: nice: 40 is out of range");
+}
@@ -0,0 +1,63 @@
+// The generated items can be renamed and made less visible:
+//
+//   - #[builder(name = "...")] names the builder type, and its error type is
+//     named after it, as in ConfigFactoryError.
+//   - #[builder(constructor = "...")] names the method that creates the
+//     builder, instead of builder().
+//   - #[builder(vis = "...")] sets the visibility of the builder, its error type
+//     and every generated method, so that an internal type does not leak a
+//     `pub` builder. A field's setter can be given its own visibility with
+//     #[builder(vis = "...")] on the field.
+//
+// The setters carry the doc comments of their fields, so the documented fields
+// of a struct give documented builder methods.
+
+mod config {
+    use derive_builder::Builder;
+
+    #[derive(Builder, Debug)]
+    #[builder(name = "ConfigFactory", vis = "pub(crate)", constructor = "factory")]
+    pub struct Config {
+        /// Host to connect to.
+        pub host: String,
+        /// Filled in by this module only.
+        #[builder(vis = "")]
+        pub token: String,
+    }
+
+    pub(crate) fn with_token(factory: &mut ConfigFactory) {
+        factory.token("secret".to_owned());
+    }
+}
+
+mod handle {
+    use derive_builder::Builder;
+
+    #[derive(Builder)]
+    #[builder(typestate, vis = "pub(crate)", constructor = "new_builder")]
+    pub struct Handle {
+        pub id: u32,
+    }
+}
+
+use config::{Config, ConfigFactory, ConfigFactoryError};
+
+fn main() {
+    let mut factory: ConfigFactory = Config::factory();
+    factory.host("example.com".to_owned());
+    config::with_token(&mut factory);
+    let config = factory.build().unwrap();
+    assert_eq!(config.token, "secret");
+
+    let err = Config::factory().build().unwrap_err();
+    assert_eq!(
+        err,
+        ConfigFactoryError::Multiple(vec![
+            ConfigFactoryError::MissingHost,
+            ConfigFactoryError::MissingToken,
+        ])
+    );
+
+    let handle = handle::Handle::new_builder().id(7).build();
+    assert_eq!(handle.id, 7);
+}
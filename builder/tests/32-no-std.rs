@@ -0,0 +1,44 @@
+// With #[builder(no_std)] the generated code takes everything from core and
+// alloc rather than std, for use in crates that are #![no_std]. The crate
+// still needs `extern crate alloc;` for the Vec and String of the error type.
+//
+// This test links std only under another name, so any path through `std` in
+// the generated code would fail to resolve.
+
+#![no_std]
+
+extern crate alloc;
+extern crate std as real_std;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(no_std, validate = "Packet::check")]
+pub struct Packet {
+    id: u16,
+    #[builder(each = "byte")]
+    payload: Vec<u8>,
+    label: Option<String>,
+    std: bool,
+}
+
+impl Packet {
+    fn check(&self) -> Result<(), String> {
+        if self.payload.len() > 4 {
+            return Err(String::from("payload too long"));
+        }
+        Ok(())
+    }
+}
+
+fn main() {
+    let packet = Packet::builder().id(1).byte(0xff).std(false).build().unwrap();
+    assert_eq!(packet.payload, [0xff]);
+    assert_eq!(packet.label, None);
+
+    let err = Packet::builder().build().unwrap_err();
+    assert_eq!(err.missing_fields(), ["id", "std"]);
+    real_std::println!("{}", err);
+}
@@ -0,0 +1,49 @@
+// #[builder(build_error = "...")] makes build() return an error type of the
+// caller's choosing. The builder's own error type is still generated, and is
+// converted into the chosen one through a From impl that the caller provides,
+// so builders fit into an application's existing error handling with `?`.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(build_error = "AppError", validate = "Server::check")]
+pub struct Server {
+    host: String,
+    port: u16,
+}
+
+impl Server {
+    fn check(&self) -> Result<(), String> {
+        if self.port == 0 {
+            return Err("port must not be zero".to_owned());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AppError {
+    Config(String),
+}
+
+impl From<ServerBuilderError> for AppError {
+    fn from(error: ServerBuilderError) -> Self {
+        AppError::Config(error.to_string())
+    }
+}
+
+fn load() -> Result<Server, AppError> {
+    let server = Server::builder().host("localhost".to_owned()).port(0).build()?;
+    Ok(server)
+}
+
+fn main() {
+    let err = Server::builder().build().unwrap_err();
+    assert_eq!(err, AppError::Config("missing fields: host, port".to_owned()));
+
+    let err = Server::builder().port(80).build().unwrap_err();
+    assert_eq!(err, AppError::Config("missing field: host".to_owned()));
+
+    let err = load().unwrap_err();
+    assert_eq!(err, AppError::Config("validation failed: port must not be zero".to_owned()));
+}
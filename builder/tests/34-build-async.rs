@@ -0,0 +1,108 @@
+// Fields that are expensive to produce can be given an async initializer with
+// #[builder(default_async = "...")]: an `async fn() -> Result<T, E>` for some
+// E that implements Display. Such a field is still required by build(), but
+// the builder also gets
+//
+//     pub async fn build_async(&mut self) -> Result<Server, ServerBuilderError>
+//
+// which awaits the initializers of all the unset fields concurrently before
+// building. A failed initializer is reported as an Initialization error, with
+// the name of its field.
+//
+// The generated code does not depend on any async runtime, so this test drives
+// build_async() with a minimal executor of its own.
+
+use derive_builder::Builder;
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+#[derive(Builder, Debug)]
+pub struct Server {
+    host: String,
+    #[builder(default_async = "open_pool")]
+    pool: Pool,
+    #[builder(default_async = "load_certificate")]
+    certificate: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pool {
+    connections: usize,
+}
+
+thread_local! {
+    static LOG: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+    static FAIL: RefCell<bool> = const { RefCell::new(false) };
+}
+
+fn log(event: &'static str) {
+    LOG.with(|log| log.borrow_mut().push(event));
+}
+
+async fn open_pool() -> Result<Pool, String> {
+    log("pool started");
+    yield_now().await;
+    log("pool finished");
+    Ok(Pool { connections: 4 })
+}
+
+async fn load_certificate() -> Result<String, String> {
+    log("certificate started");
+    yield_now().await;
+    log("certificate finished");
+    if FAIL.with(|fail| *fail.borrow()) {
+        return Err("file not found".to_owned());
+    }
+    Ok("-----BEGIN CERTIFICATE-----".to_owned())
+}
+
+// Returns Pending the first time it is polled.
+async fn yield_now() {
+    let mut yielded = false;
+    std::future::poll_fn(|cx| {
+        if yielded {
+            return Poll::Ready(());
+        }
+        yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    })
+    .await
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+fn main() {
+    let mut builder = Server::builder();
+    builder.host("localhost".to_owned());
+    let err = builder.build().unwrap_err();
+    assert_eq!(err.missing_fields(), ["pool", "certificate"]);
+
+    let server = block_on(builder.build_async()).unwrap();
+    assert_eq!(server.pool, Pool { connections: 4 });
+    assert_eq!(server.certificate, "-----BEGIN CERTIFICATE-----");
+    let log = LOG.with(|log| log.take());
+    assert_eq!(
+        log,
+        ["pool started", "certificate started", "pool finished", "certificate finished"],
+    );
+
+    // Fields that were set are not initialized again.
+    let mut builder = Server::builder();
+    builder.host("localhost".to_owned()).pool(Pool { connections: 1 });
+    FAIL.with(|fail| *fail.borrow_mut() = true);
+    let err = block_on(builder.build_async()).unwrap_err();
+    assert_eq!(err, ServerBuilderError::Initialization("certificate: file not found".to_owned()),);
+    assert_eq!(err.to_string(), "initialization failed: certificate: file not found");
+    assert_eq!(LOG.with(|log| log.take()), ["certificate started", "certificate finished"]);
+}
@@ -0,0 +1,98 @@
+// The builder implements Debug, showing what has been set so far. The value of
+// a field marked #[builder(secret)] is never printed; only whether it is set:
+//
+//     CommandBuilder { executable: Some("cargo"), token: Some(<redacted>), .. }
+//
+// The builder can also say which required fields are still unset, in
+// declaration order, so a caller can prompt for them before calling build():
+//
+//     impl CommandBuilder {
+//         pub fn missing_fields(&self) -> Vec<&'static str> { ... }
+//     }
+//
+// This is a Vec rather than a static slice because the answer depends on
+// which setters have been called. A sub-builder with anything missing is
+// listed under the outer field's name.
+//
+// #[builder(no_debug)] leaves the Debug impl out, for a builder that should
+// be printed some other way.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(secret)]
+    token: String,
+    #[builder(secret)]
+    password: Option<String>,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(each = "env", secret)]
+    env: Vec<String>,
+    #[builder(sub_builder)]
+    limits: Limits,
+}
+
+#[derive(Builder, Debug)]
+pub struct Limits {
+    memory: u64,
+}
+
+// A field type that is not Debug leaves the builder without Debug, but still
+// lets it be derived.
+#[derive(Clone)]
+pub struct Opaque;
+
+#[derive(Builder)]
+pub struct Wrapper {
+    opaque: Opaque,
+}
+
+#[derive(Builder)]
+#[builder(no_debug)]
+pub struct Login {
+    user: String,
+}
+
+impl std::fmt::Debug for LoginBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "LoginBuilder missing {:?}", self.missing_fields())
+    }
+}
+
+fn main() {
+    let mut builder = Command::builder();
+    assert_eq!(builder.missing_fields(), ["executable", "token", "limits"]);
+    assert_eq!(
+        format!("{:?}", builder),
+        "CommandBuilder { executable: None, token: None, password: None, args: None, \
+         env: <redacted>, limits: LimitsBuilder { memory: None } }",
+    );
+
+    builder.executable("cargo".to_owned());
+    builder.token("hunter2".to_owned());
+    builder.arg("build".to_owned());
+    builder.env("API_KEY=hunter2".to_owned());
+    builder.limits().memory(1 << 30);
+    assert!(builder.missing_fields().is_empty());
+
+    let debug = format!("{:?}", builder);
+    assert_eq!(
+        debug,
+        "CommandBuilder { executable: Some(\"cargo\"), token: Some(<redacted>), \
+         password: None, args: Some([\"build\"]), env: <redacted>, \
+         limits: LimitsBuilder { memory: Some(1073741824) } }",
+    );
+    assert!(!debug.contains("hunter2"));
+
+    let mut builder = Wrapper::builder();
+    assert_eq!(builder.missing_fields(), ["opaque"]);
+    builder.opaque(Opaque);
+    assert!(builder.missing_fields().is_empty());
+
+    let mut builder = Login::builder();
+    assert_eq!(format!("{:?}", builder), "LoginBuilder missing [\"user\"]");
+    builder.user("root".to_owned());
+    assert_eq!(format!("{:?}", builder), "LoginBuilder missing []");
+}
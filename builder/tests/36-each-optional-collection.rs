@@ -0,0 +1,142 @@
+// #[builder(each = "...")] also works on an Option<C> field. The field stays
+// None until the first item is added, so "never set" and "set to nothing"
+// remain distinct, and the all-at-once setter takes C itself.
+//
+// Collection types are recognized however they are spelled, whether Vec<T>,
+// std::vec::Vec<T> or alloc::vec::Vec<T>. A type alias hides what it stands
+// for from the macro; it still works through the alias' IntoIterator item,
+// and #[builder(collection = "...")] names the collection behind it so that
+// the items get their plain type:
+//
+//     type Args = SmallVec<[String; 4]>;
+//
+//     #[builder(each = "arg", collection = "SmallVec<[String; 4]>")]
+//     args: Option<Args>,
+
+extern crate alloc;
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Command {
+    #[builder(each = "var")]
+    env: Option<Vec<String>>,
+    #[builder(each = "arg", collection = "SmallVec<[String; 4]>", setter(into))]
+    args: Option<Args>,
+    #[builder(each = "flag", collection = "SmallVec<[char; 4]>")]
+    flags: Flags,
+    #[builder(each = "path")]
+    paths: std::vec::Vec<String>,
+    #[builder(each = "include")]
+    includes: alloc::vec::Vec<String>,
+    #[builder(each = "feature")]
+    features: Features,
+}
+
+pub type Args = SmallVec<[String; 4]>;
+pub type Flags = SmallVec<[char; 4]>;
+pub type Features = Vec<String>;
+
+// Stands in for the smallvec crate: a vector with room for the items of an
+// array type inline.
+pub trait Array {
+    type Item;
+}
+
+impl<T, const N: usize> Array for [T; N] {
+    type Item = T;
+}
+
+pub struct SmallVec<A: Array>(Vec<A::Item>);
+
+impl<A: Array> Default for SmallVec<A> {
+    fn default() -> Self {
+        SmallVec(Vec::new())
+    }
+}
+
+impl<A: Array> Clone for SmallVec<A>
+where
+    A::Item: Clone,
+{
+    fn clone(&self) -> Self {
+        SmallVec(self.0.clone())
+    }
+}
+
+impl<A: Array> std::fmt::Debug for SmallVec<A>
+where
+    A::Item: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<A: Array> PartialEq for SmallVec<A>
+where
+    A::Item: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<A: Array> Extend<A::Item> for SmallVec<A> {
+    fn extend<I: IntoIterator<Item = A::Item>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+impl<A: Array> IntoIterator for SmallVec<A> {
+    type Item = A::Item;
+    type IntoIter = std::vec::IntoIter<A::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+fn small_vec<A: Array>(items: impl IntoIterator<Item = A::Item>) -> SmallVec<A> {
+    SmallVec(items.into_iter().collect())
+}
+
+fn main() {
+    let command = Command::builder().build().unwrap();
+    assert_eq!(command.env, None);
+    assert_eq!(command.args, None);
+
+    let command = Command::builder()
+        .var("RUST_LOG=debug".to_owned())
+        .var("RUST_BACKTRACE=1".to_owned())
+        .arg("build")
+        .arg("--release")
+        .flag('v')
+        .path("/usr/bin".to_owned())
+        .include("src".to_owned())
+        .feature("serde".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(command.env.unwrap(), ["RUST_LOG=debug", "RUST_BACKTRACE=1"]);
+    assert_eq!(command.args, Some(small_vec(["build".to_owned(), "--release".to_owned()])));
+    assert_eq!(command.flags, small_vec(['v']));
+    assert_eq!(command.paths, ["/usr/bin"]);
+    assert_eq!(command.includes, ["src"]);
+    assert_eq!(command.features, ["serde"]);
+
+    // Setting the whole collection, even to nothing, makes it Some.
+    let command = Command::builder().env(Vec::new()).build().unwrap();
+    assert_eq!(command.env, Some(Vec::new()));
+
+    let mut base = Command::builder();
+    base.var("A=1".to_owned());
+    let mut overrides = Command::builder();
+    overrides.var("B=2".to_owned());
+    let command = base.merge(overrides).build().unwrap();
+    assert_eq!(command.env.unwrap(), ["A=1", "B=2"]);
+
+    let mut base = Command::builder();
+    base.var("A=1".to_owned());
+    let command = base.merge(Command::builder()).build().unwrap();
+    assert_eq!(command.env.unwrap(), ["A=1"]);
+}
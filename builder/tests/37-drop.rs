@@ -0,0 +1,49 @@
+// From<Guard> for GuardBuilder moves the fields out of the value, which Rust
+// does not allow for a type that implements Drop. #[builder(no_from)] leaves
+// that impl out so such a type can still derive Builder; to_builder() clones
+// the fields instead and is still there.
+//
+// Such a type can also be the field of another builder with
+// #[builder(sub_builder)]. The outer to_builder() then cannot be called,
+// since it would convert the field into its builder, but everything else
+// works.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Clone)]
+#[builder(no_from)]
+pub struct Guard {
+    name: String,
+    #[builder(each = "lock")]
+    locks: Vec<u32>,
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {}
+}
+
+#[derive(Builder)]
+#[builder(no_from)]
+pub struct Session {
+    user: String,
+    #[builder(sub_builder)]
+    guard: Guard,
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {}
+}
+
+fn main() {
+    let guard = Guard::builder().name("db".to_owned()).lock(1).build().unwrap();
+    let other = guard.to_builder().lock(2).build().unwrap();
+    assert_eq!(other.name, "db");
+    assert_eq!(other.locks, [1, 2]);
+
+    let mut builder = Session::builder();
+    builder.user("root".to_owned());
+    builder.guard().name("db".to_owned()).lock(3);
+    let session = builder.build().unwrap();
+    assert_eq!(session.user, "root");
+    assert_eq!(session.guard.locks, [3]);
+}
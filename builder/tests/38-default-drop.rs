@@ -0,0 +1,67 @@
+// #[builder(default)] on a struct takes every unset field from the struct's
+// own Default value. The fields are cloned out of that value rather than moved
+// out of it, so the struct may implement Drop (with #[builder(no_from)], as in
+// 37-drop), and the fields taken from it need to be Clone.
+
+use derive_builder::Builder;
+use std::cell::Cell;
+
+thread_local! {
+    static DROPPED: Cell<usize> = const { Cell::new(0) };
+}
+
+#[derive(Builder)]
+#[builder(default, no_from, pattern = "owned")]
+pub struct Connection<T> {
+    host: String,
+    port: u16,
+    options: T,
+    #[builder(skip)]
+    retries: u32,
+}
+
+impl<T: Default> Default for Connection<T> {
+    fn default() -> Self {
+        Connection { host: "localhost".to_owned(), port: 5432, options: T::default(), retries: 3 }
+    }
+}
+
+impl<T> Drop for Connection<T> {
+    fn drop(&mut self) {
+        DROPPED.with(|dropped| dropped.set(dropped.get() + 1));
+    }
+}
+
+#[derive(Builder)]
+#[builder(default, no_from, typestate)]
+pub struct Session {
+    user: String,
+    timeout: u32,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Session { user: "guest".to_owned(), timeout: 30 }
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {}
+}
+
+fn main() {
+    let connection = Connection::<Vec<u8>>::builder().port(6543).build().unwrap();
+    assert_eq!(connection.host, "localhost");
+    assert_eq!(connection.port, 6543);
+    assert!(connection.options.is_empty());
+    assert_eq!(connection.retries, 3);
+
+    // The default value that fields were cloned out of was dropped too.
+    assert_eq!(DROPPED.with(Cell::get), 1);
+    drop(connection);
+    assert_eq!(DROPPED.with(Cell::get), 2);
+
+    let session = Session::builder().user("admin".to_owned()).build();
+    assert_eq!(session.user, "admin");
+    assert_eq!(session.timeout, 30);
+}
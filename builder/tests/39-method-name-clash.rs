@@ -0,0 +1,36 @@
+// The builder defines build(), merge() and missing_fields() itself, and
+// build_async() when a field has #[builder(default_async = "...")], so no
+// setter can be named like any of them. Such a name is reported at the field,
+// and can be changed with #[builder(rename = "...")], or
+// #[builder(each = "...")] for the one-at-a-time setter of a collection.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Config {
+    merge: bool,
+    #[builder(each = "build")]
+    builds: Vec<String>,
+    #[builder(rename = "merge_strategy")]
+    strategy: String,
+    missing_fields: Vec<String>,
+}
+
+#[derive(Builder)]
+pub struct Renamed {
+    #[builder(rename = "merge_enabled")]
+    merge: bool,
+}
+
+#[derive(Builder)]
+pub struct Service {
+    #[builder(default_async = "connect")]
+    pool: u32,
+    build_async: bool,
+}
+
+async fn connect() -> Result<u32, String> {
+    Ok(1)
+}
+
+fn main() {}
@@ -0,0 +1,93 @@
+// A sub-builder does not depend on how its own builder is set up: the field
+// type can rename its constructor, give build() its own error type or use
+// the owned pattern, and the outer builder still creates and builds it the
+// same way. The outer error wraps the inner builder's own error type.
+//
+// Every builder is Clone when the storage of its fields is, so an immutable
+// outer builder can copy a sub-builder that uses another pattern.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Server {
+    host: String,
+    #[builder(sub_builder)]
+    tls: Tls,
+    #[builder(sub_builder)]
+    limits: Limits,
+    #[builder(sub_builder)]
+    log: Log,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(constructor = "new_builder")]
+pub struct Tls {
+    cert_path: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct LimitsError(String);
+
+impl From<LimitsBuilderError> for LimitsError {
+    fn from(error: LimitsBuilderError) -> Self {
+        LimitsError(error.to_string())
+    }
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(build_error = "LimitsError")]
+pub struct Limits {
+    memory: u64,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(pattern = "owned")]
+pub struct Log {
+    level: String,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(pattern = "immutable")]
+pub struct Deployment {
+    name: String,
+    #[builder(sub_builder)]
+    limits: Limits,
+}
+
+fn main() {
+    let mut builder = Server::builder();
+    builder.host("example.com".to_owned());
+    builder.tls().cert_path("/etc/cert.pem".to_owned());
+    builder.limits().memory(1 << 20);
+    *builder.log() = Log::builder().level("info".to_owned());
+    let server = builder.build().unwrap();
+    assert_eq!(server.tls, Tls { cert_path: "/etc/cert.pem".to_owned() });
+    assert_eq!(server.limits, Limits { memory: 1 << 20 });
+    assert_eq!(server.log, Log { level: "info".to_owned() });
+
+    let err = Server::builder().build().unwrap_err();
+    assert_eq!(err.missing_fields(), ["host", "tls.cert_path", "limits.memory", "log.level"]);
+
+    let mut builder = Server::builder();
+    builder.host("example.com".to_owned());
+    builder.tls().cert_path("/etc/cert.pem".to_owned());
+    builder.limits();
+    *builder.log() = Log::builder().level("info".to_owned());
+    let err = builder.build().unwrap_err();
+    assert_eq!(err, ServerBuilderError::NestedLimits(LimitsBuilderError::MissingMemory));
+
+    let mut builder = Deployment::builder();
+    builder.limits().memory(1 << 30);
+    let production = builder.name("production".to_owned());
+    let staging = production.name("staging".to_owned());
+    assert_eq!(production.build().unwrap().limits, Limits { memory: 1 << 30 });
+    let staging = staging.build().unwrap();
+    assert_eq!(staging.name, "staging");
+    assert_eq!(staging.limits, Limits { memory: 1 << 30 });
+
+    // The inner builders keep their own API.
+    let tls = Tls::new_builder().cert_path("/etc/cert.pem".to_owned()).build().unwrap();
+    assert_eq!(tls.cert_path, "/etc/cert.pem");
+    let err = Limits::builder().build().unwrap_err();
+    assert_eq!(err, LimitsError("missing field: memory".to_owned()));
+}
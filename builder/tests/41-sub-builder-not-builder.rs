@@ -0,0 +1,16 @@
+// A #[builder(sub_builder)] field whose type does not derive Builder is
+// reported at the field type.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Server {
+    #[builder(sub_builder)]
+    tls: Tls,
+}
+
+pub struct Tls {
+    pub cert_path: String,
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+// #[builder(no_std)] only changes the paths the macro writes itself. Types and
+// expressions from the input are used as written, so a crate that has std
+// can still name its own field types and defaults through it.
+
+extern crate alloc;
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(no_std)]
+pub struct Cache {
+    #[builder(each = "entry")]
+    entries: std::collections::BTreeMap<String, u32>,
+    #[builder(default = "std::sync::Arc::new(std::string::String::from(\"cache\"))")]
+    name: std::sync::Arc<String>,
+}
+
+fn main() {
+    let cache = Cache::builder().entry("a".to_owned(), 1).build().unwrap();
+    assert_eq!(cache.entries["a"], 1);
+    assert_eq!(*cache.name, "cache");
+}
@@ -0,0 +1,52 @@
+// The setters of a field are free to take an argument named `builder`,
+// whatever the pattern, and so is the one-at-a-time setter of a collection.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Mutable {
+    builder: String,
+}
+
+#[derive(Builder)]
+pub struct Repeated {
+    #[builder(each = "builder")]
+    builders: Vec<String>,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Owned {
+    builder: String,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "immutable")]
+pub struct Immutable {
+    builder: String,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Typestate {
+    name: String,
+    builder: Option<String>,
+}
+
+fn main() {
+    let mutable = Mutable::builder().builder("a".to_owned()).build().unwrap();
+    assert_eq!(mutable.builder, "a");
+
+    let repeated = Repeated::builder().builder("a".to_owned()).build().unwrap();
+    assert_eq!(repeated.builders, ["a"]);
+
+    let owned = Owned::builder().builder("b".to_owned()).build().unwrap();
+    assert_eq!(owned.builder, "b");
+
+    let immutable = Immutable::builder().builder("c".to_owned()).build().unwrap();
+    assert_eq!(immutable.builder, "c");
+
+    let typestate = Typestate::builder().builder("d".to_owned()).name("e".to_owned()).build();
+    assert_eq!(typestate.builder.as_deref(), Some("d"));
+    assert_eq!(typestate.name, "e");
+}
@@ -0,0 +1,16 @@
+// A typestate builder has no Debug impl and no merge(), so the attributes
+// that only change those are rejected rather than ignored.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate, no_debug)]
+pub struct Command {
+    executable: String,
+    #[builder(secret)]
+    token: Option<String>,
+    #[builder(each = "arg", merge = "replace")]
+    args: Vec<String>,
+}
+
+fn main() {}
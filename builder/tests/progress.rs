@@ -3,11 +3,46 @@ fn tests() {
     let t = trybuild::TestCases::new();
     t.pass("tests/01-parse.rs");
     t.pass("tests/02-create-builder.rs");
-    //t.pass("tests/03-call-setters.rs");
-    //t.pass("tests/04-call-build.rs");
-    //t.pass("tests/05-method-chaining.rs");
-    //t.pass("tests/06-optional-field.rs");
-    //t.pass("tests/07-repeated-field.rs");
-    //t.compile_fail("tests/08-unrecognized-attribute.rs");
-    //t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/03-call-setters.rs");
+    t.pass("tests/04-call-build.rs");
+    t.pass("tests/05-method-chaining.rs");
+    t.pass("tests/06-optional-field.rs");
+    t.pass("tests/07-repeated-field.rs");
+    t.compile_fail("tests/08-unrecognized-attribute.rs");
+    t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/10-missing-fields.rs");
+    t.pass("tests/11-typed-error.rs");
+    t.pass("tests/12-typestate.rs");
+    t.compile_fail("tests/13-typestate-missing-field.rs");
+    t.pass("tests/14-tuple-struct.rs");
+    t.pass("tests/15-enum.rs");
+    t.compile_fail("tests/16-union.rs");
+    t.pass("tests/17-generics.rs");
+    t.pass("tests/18-default.rs");
+    t.pass("tests/19-setter-into.rs");
+    t.compile_fail("tests/20-strip-option-non-option.rs");
+    t.pass("tests/21-validate.rs");
+    t.pass("tests/22-pattern.rs");
+    t.pass("tests/23-to-builder.rs");
+    t.pass("tests/24-serde.rs");
+    t.pass("tests/25-merge.rs");
+    t.compile_fail("tests/26-merge-without-each.rs");
+    t.pass("tests/27-each-collections.rs");
+    t.pass("tests/28-sub-builder.rs");
+    t.compile_fail("tests/29-attribute-errors.rs");
+    t.pass("tests/30-field-options.rs");
+    t.pass("tests/31-visibility-and-naming.rs");
+    t.pass("tests/32-no-std.rs");
+    t.pass("tests/33-build-error.rs");
+    t.pass("tests/34-build-async.rs");
+    t.pass("tests/35-builder-debug.rs");
+    t.pass("tests/36-each-optional-collection.rs");
+    t.pass("tests/37-drop.rs");
+    t.pass("tests/38-default-drop.rs");
+    t.compile_fail("tests/39-method-name-clash.rs");
+    t.pass("tests/40-sub-builder-options.rs");
+    t.compile_fail("tests/41-sub-builder-not-builder.rs");
+    t.pass("tests/42-no-std-user-paths.rs");
+    t.pass("tests/43-field-named-builder.rs");
+    t.compile_fail("tests/44-typestate-unsupported.rs");
 }
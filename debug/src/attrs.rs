@@ -0,0 +1,130 @@
+use syn::punctuated::Punctuated;
+
+// Options given on the struct with #[debug(...)].
+pub(crate) struct ContainerAttrs {
+    // Where-clause predicates from #[debug(bound = "...")], used instead of
+    // any that would be inferred from the fields.
+    pub(crate) bound: Option<Vec<syn::WherePredicate>>,
+}
+
+impl ContainerAttrs {
+    pub(crate) fn parse(attrs: &[syn::Attribute]) -> Result<Self, syn::Error> {
+        let mut bound = None;
+        for attr in debug_attrs(attrs) {
+            if let syn::Meta::NameValue(meta) = &attr.meta {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    "#[debug = \"...\"] is only supported on fields",
+                ));
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("bound") {
+                    set_once(&mut bound, &meta, parse_bound(&meta)?)
+                } else {
+                    Err(meta.error("unknown debug attribute, expected `bound = \"...\"`"))
+                }
+            })?;
+        }
+        Ok(ContainerAttrs { bound })
+    }
+}
+
+// Options given on a field with #[debug = "..."] or #[debug(...)].
+pub(crate) struct FieldAttrs {
+    // Format string that the value is printed with, instead of its Debug impl.
+    pub(crate) format: Option<syn::LitStr>,
+    // Where-clause predicates from #[debug(bound = "...")], used instead of
+    // any that would be inferred from this field.
+    pub(crate) bound: Option<Vec<syn::WherePredicate>>,
+    // Leave the field out of the output, from #[debug(skip)].
+    pub(crate) skip: bool,
+    // Print "***" instead of the value, from #[debug(redact)].
+    pub(crate) redact: bool,
+}
+
+impl FieldAttrs {
+    pub(crate) fn parse(field: &syn::Field) -> Result<Self, syn::Error> {
+        let mut format = None;
+        let mut bound = None;
+        let mut skip = None;
+        let mut redact = None;
+        for attr in debug_attrs(&field.attrs) {
+            if let syn::Meta::NameValue(meta) = &attr.meta {
+                let syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit), .. }) = &meta.value
+                else {
+                    return Err(syn::Error::new_spanned(&meta.value, "expected a format string"));
+                };
+                if format.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "duplicate #[debug = \"...\"] attribute",
+                    ));
+                }
+                format = Some(lit.clone());
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("bound") {
+                    set_once(&mut bound, &meta, parse_bound(&meta)?)
+                } else if meta.path.is_ident("skip") {
+                    set_once(&mut skip, &meta, meta.path.clone())
+                } else if meta.path.is_ident("redact") {
+                    set_once(&mut redact, &meta, meta.path.clone())
+                } else {
+                    Err(meta.error(
+                        "unknown debug attribute, expected `bound = \"...\"`, `skip` or `redact`",
+                    ))
+                }
+            })?;
+        }
+
+        // Neither a skipped nor a redacted field prints its value, so nothing
+        // about how to print it applies.
+        if let (Some(skip), Some(_)) = (&skip, &redact) {
+            return Err(syn::Error::new_spanned(skip, "`skip` cannot be combined with `redact`"));
+        }
+        if let Some(path) = skip.as_ref().or(redact.as_ref()) {
+            let name = path.get_ident().unwrap();
+            if format.is_some() {
+                return Err(syn::Error::new_spanned(
+                    path,
+                    format!("`{}` cannot be combined with #[debug = \"...\"]", name),
+                ));
+            }
+            if bound.is_some() {
+                return Err(syn::Error::new_spanned(
+                    path,
+                    format!("`{}` cannot be combined with `bound`", name),
+                ));
+            }
+        }
+
+        Ok(FieldAttrs { format, bound, skip: skip.is_some(), redact: redact.is_some() })
+    }
+}
+
+fn debug_attrs(attrs: &[syn::Attribute]) -> impl Iterator<Item = &syn::Attribute> {
+    attrs.iter().filter(|attr| attr.path().is_ident("debug"))
+}
+
+fn set_once<T>(
+    slot: &mut Option<T>,
+    meta: &syn::meta::ParseNestedMeta,
+    value: T,
+) -> Result<(), syn::Error> {
+    if slot.is_some() {
+        let name = meta.path.get_ident().unwrap();
+        return Err(meta.error(format!("duplicate debug attribute `{}`", name)));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+// `bound = "T::Value: Debug, U: Debug"`. An empty string asks for no bounds
+// at all. Errors in the predicates are reported at the string.
+fn parse_bound(meta: &syn::meta::ParseNestedMeta) -> Result<Vec<syn::WherePredicate>, syn::Error> {
+    let lit: syn::LitStr = meta.value()?.parse()?;
+    let predicates =
+        lit.parse_with(Punctuated::<syn::WherePredicate, syn::Token![,]>::parse_terminated)?;
+    Ok(predicates.into_iter().collect())
+}
@@ -0,0 +1,79 @@
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use quote::{quote, ToTokens};
+
+// The types that must be Debug for a field of type `ty` to be Debug, given the
+// type parameters of the input. Like std's derive, a generic type such as
+// Vec<T> or Box<Two<T>> is assumed to be Debug whenever its type arguments
+// are, so the bound lands on T itself:
+//
+//   - T and T::Value are bounded as they are.
+//   - <T as Trait>::Assoc, or any qualified path involving a parameter, is
+//     bounded as a whole.
+//   - PhantomData<T> is Debug for every T, and contributes nothing.
+//   - References, arrays, slices and tuples are Debug when their elements are.
+//   - Raw pointers and fn pointers are Debug regardless of what they point to.
+pub(crate) fn debug_types(params: &[&syn::Ident], ty: &syn::Type, types: &mut Vec<syn::Type>) {
+    match ty {
+        syn::Type::Path(type_path) => {
+            if let Some(qself) = &type_path.qself {
+                if mentions_param(params, qself.ty.to_token_stream()) {
+                    push_type(types, ty);
+                }
+                return;
+            }
+
+            let path = &type_path.path;
+            let first = &path.segments[0].ident;
+            if path.leading_colon.is_none() && params.contains(&first) {
+                push_type(types, ty);
+                return;
+            }
+
+            if path.segments.last().unwrap().ident == "PhantomData" {
+                return;
+            }
+            for segment in &path.segments {
+                let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+                    continue;
+                };
+                for arg in &args.args {
+                    if let syn::GenericArgument::Type(arg) = arg {
+                        debug_types(params, arg, types);
+                    }
+                }
+            }
+        }
+        syn::Type::Reference(reference) => debug_types(params, &reference.elem, types),
+        syn::Type::Array(array) => debug_types(params, &array.elem, types),
+        syn::Type::Slice(slice) => debug_types(params, &slice.elem, types),
+        syn::Type::Paren(paren) => debug_types(params, &paren.elem, types),
+        syn::Type::Group(group) => debug_types(params, &group.elem, types),
+        syn::Type::Tuple(tuple) => {
+            for elem in &tuple.elems {
+                debug_types(params, elem, types);
+            }
+        }
+        _ => {}
+    }
+}
+
+// The `#ty: Debug` predicates for the types gathered by debug_types.
+pub(crate) fn predicates(types: &[syn::Type]) -> impl Iterator<Item = syn::WherePredicate> + '_ {
+    types.iter().map(|ty| syn::parse_quote!(#ty: std::fmt::Debug))
+}
+
+// Adds `ty` unless it is already there, as when two fields are both T.
+fn push_type(types: &mut Vec<syn::Type>, ty: &syn::Type) {
+    let tokens = quote!(#ty).to_string();
+    if !types.iter().any(|other| quote!(#other).to_string() == tokens) {
+        types.push(ty.clone());
+    }
+}
+
+fn mentions_param(params: &[&syn::Ident], tokens: TokenStream2) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(ident) => params.contains(&&ident),
+        TokenTree::Group(group) => mentions_param(params, group.stream()),
+        _ => false,
+    })
+}
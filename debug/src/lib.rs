@@ -1,8 +1,168 @@
 use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Data, DeriveInput, Fields};
 
-#[proc_macro_derive(CustomDebug)]
+use crate::attrs::{ContainerAttrs, FieldAttrs};
+
+mod attrs;
+mod bound;
+
+#[proc_macro_derive(CustomDebug, attributes(debug))]
 pub fn derive(input: TokenStream) -> TokenStream {
-    let _ = input;
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand(input) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(e) => TokenStream::from(e.to_compile_error()),
+    }
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2, syn::Error> {
+    let type_name = &input.ident;
+    let attrs = ContainerAttrs::parse(&input.attrs)?;
+    let mut bounds = Bounds {
+        params: input.generics.type_params().map(|param| &param.ident).collect(),
+        inferred: Vec::new(),
+        given: Vec::new(),
+    };
+
+    // One match arm for a struct, one per variant for an enum.
+    let arms = match &input.data {
+        Data::Struct(data) => {
+            vec![debug_arm(quote!(#type_name), type_name, &data.fields, &mut bounds)?]
+        }
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .map(|variant| {
+                let variant_name = &variant.ident;
+                let path = quote!(#type_name::#variant_name);
+                debug_arm(path, variant_name, &variant.fields, &mut bounds)
+            })
+            .collect::<Result<_, _>>()?,
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(&input, "CustomDebug does not support unions"));
+        }
+    };
+
+    // Bounds given on the type replace the inferred ones, while those given
+    // on a field only replace what that field would have inferred.
+    let mut generics = input.generics.clone();
+    let predicates = &mut generics.make_where_clause().predicates;
+    match attrs.bound {
+        Some(bound) => predicates.extend(bound),
+        None => predicates.extend(bound::predicates(&bounds.inferred)),
+    }
+    predicates.extend(bounds.given);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // An enum without variants has no value to print.
+    let body = if arms.is_empty() {
+        quote!(match *self {})
+    } else {
+        quote! {
+            match self {
+                #(#arms)*
+            }
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics std::fmt::Debug for #type_name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                #body
+            }
+        }
+    })
+}
+
+// What the impl needs of the type parameters, gathered field by field.
+struct Bounds<'a> {
+    params: Vec<&'a syn::Ident>,
+    // Types inferred to need Debug, from the fields without a bound of their
+    // own.
+    inferred: Vec<syn::Type>,
+    // Predicates from #[debug(bound = "...")] on fields.
+    given: Vec<syn::WherePredicate>,
+}
+
+// The match arm that prints a struct or an enum variant, reached through
+// `path`: debug_struct for named fields, debug_tuple for unnamed fields and
+// just the name for a unit struct or variant.
+fn debug_arm(
+    path: TokenStream2,
+    name: &syn::Ident,
+    fields: &Fields,
+    bounds: &mut Bounds,
+) -> Result<TokenStream2, syn::Error> {
+    let name_string = name.to_string();
+    let mut bindings = Vec::new();
+    let mut debug_fields = Vec::new();
+    let mut skipped = false;
+
+    // Every field is printed with its own Debug impl, or through the format
+    // string of #[debug = "..."]. Only the former needs the field to be Debug;
+    // what a format string needs is up to the format string. A skipped field
+    // is left out, and a redacted one is printed as "***".
+    for (i, field) in fields.iter().enumerate() {
+        let field_attrs = FieldAttrs::parse(field)?;
+        if field_attrs.skip {
+            skipped = true;
+            bindings.push(match &field.ident {
+                Some(ident) => quote!(#ident: _),
+                None => quote!(_),
+            });
+            continue;
+        }
+
+        let binding = format_ident!("__self_{}", i);
+        let value = match &field_attrs.format {
+            Some(format) => quote!(&std::format_args!(#format, #binding)),
+            None if field_attrs.redact => quote!(&"***"),
+            None => quote!(#binding),
+        };
+        match field_attrs.bound {
+            Some(bound) => bounds.given.extend(bound),
+            None if field_attrs.format.is_none() && !field_attrs.redact => {
+                bound::debug_types(&bounds.params, &field.ty, &mut bounds.inferred);
+            }
+            None => {}
+        }
+        let binding = if field_attrs.redact { quote!(_) } else { quote!(#binding) };
+
+        match &field.ident {
+            Some(ident) => {
+                let ident_string = ident.to_string();
+                bindings.push(quote!(#ident: #binding));
+                debug_fields.push(quote!(.field(#ident_string, #value)));
+            }
+            None => {
+                bindings.push(quote!(#binding));
+                debug_fields.push(quote!(.field(#value)));
+            }
+        }
+    }
 
-    unimplemented!()
+    // Output that leaves out a field ends in `..`.
+    let finish = if skipped { quote!(finish_non_exhaustive) } else { quote!(finish) };
+    Ok(match fields {
+        Fields::Named(_) => quote! {
+            #path { #(#bindings),* } => {
+                f.debug_struct(#name_string)
+                    #(#debug_fields)*
+                    .#finish()
+            }
+        },
+        Fields::Unnamed(_) => quote! {
+            #path(#(#bindings),*) => {
+                f.debug_tuple(#name_string)
+                    #(#debug_fields)*
+                    .#finish()
+            }
+        },
+        Fields::Unit => quote! {
+            #path => f.write_str(#name_string),
+        },
+    })
 }
@@ -0,0 +1,57 @@
+// Beyond the cases of the previous tests, the inferred bounds look through
+// the shapes a field type commonly takes: Box<T>, Vec<Option<T>>, references,
+// arrays, slices and tuples bound their element types, and a qualified path
+// such as <T as Trait>::Assoc is bounded as a whole.
+//
+// A type parameter that only appears inside PhantomData is never bounded, so
+// Wrapper<NotDebug, u8> below is still Debug.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+pub trait Trait {
+    type Assoc;
+}
+
+#[derive(CustomDebug)]
+pub struct Wrapper<'a, M, T, U, V, W, X: Trait> {
+    marker: PhantomData<(M, fn() -> M)>,
+    boxed: Box<T>,
+    nested: Vec<Option<U>>,
+    reference: &'a V,
+    array: [(W, u8); 2],
+    qualified: <X as Trait>::Assoc,
+    pointer: *const M,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct NotDebug;
+
+    struct Id;
+
+    impl Trait for Id {
+        type Assoc = u8;
+    }
+
+    assert_debug::<Wrapper<NotDebug, u8, u8, u8, u8, Id>>();
+
+    let value = 3;
+    let wrapper = Wrapper::<NotDebug, _, _, _, _, Id> {
+        marker: PhantomData,
+        boxed: Box::new(1),
+        nested: vec![Some(2), None],
+        reference: &value,
+        array: [('a', 4), ('b', 5)],
+        qualified: 6,
+        pointer: std::ptr::null(),
+    };
+    let debug = format!("{:?}", wrapper);
+    assert!(debug.starts_with("Wrapper { marker: PhantomData<"));
+    assert!(debug.ends_with(
+        "boxed: 1, nested: [Some(2), None], reference: 3, \
+         array: [('a', 4), ('b', 5)], qualified: 6, pointer: 0x0 }",
+    ));
+}
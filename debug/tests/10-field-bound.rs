@@ -0,0 +1,45 @@
+// #[debug(bound = "...")] on a field replaces only the bounds that would be
+// inferred from that field. Here `field` would otherwise ask for T: Debug,
+// which Id is not, while U is still inferred to need Debug from `normal`:
+//
+//     impl<T: Trait, U> Debug for Wrapper<T, U>
+//     where
+//         U: Debug,
+//         T::Value: Debug,
+//     {...}
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+pub trait Trait {
+    type Value;
+}
+
+#[derive(CustomDebug)]
+pub struct Wrapper<T: Trait, U> {
+    #[debug(bound = "T::Value: Debug")]
+    field: Field<T>,
+    normal: U,
+}
+
+#[derive(CustomDebug)]
+pub struct Field<T: Trait> {
+    values: Vec<T::Value>,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct Id;
+
+    impl Trait for Id {
+        type Value = u8;
+    }
+
+    assert_debug::<Wrapper<Id, u8>>();
+
+    let wrapper = Wrapper::<Id, _> { field: Field { values: vec![1, 2] }, normal: "normal" };
+    let debug = format!("{:?}", wrapper);
+    let expected = r#"Wrapper { field: Field { values: [1, 2] }, normal: "normal" }"#;
+    assert_eq!(debug, expected);
+}
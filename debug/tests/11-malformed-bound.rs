@@ -0,0 +1,18 @@
+// A bound that does not parse as where-clause predicates is reported at the
+// string it was given in, whether on the struct or on a field.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(bound = "T: Debug;")]
+pub struct Container<T> {
+    value: T,
+}
+
+#[derive(CustomDebug)]
+pub struct Field<T> {
+    #[debug(bound = "T Debug")]
+    value: T,
+}
+
+fn main() {}
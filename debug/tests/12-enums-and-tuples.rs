@@ -0,0 +1,43 @@
+// Besides structs with named fields, CustomDebug accepts the other shapes a
+// type can take, and prints them the way std's derive does: a tuple struct or
+// tuple variant through Formatter::debug_tuple, a struct variant through
+// Formatter::debug_struct, and a unit struct or unit variant as its bare name.
+// #[debug = "..."] applies to the fields of any of them.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub enum Error<T> {
+    NotFound,
+    Io(#[debug = "errno {}"] i32, T),
+    Parse {
+        line: usize,
+        #[debug = "0x{:02x}"]
+        byte: u8,
+    },
+}
+
+#[derive(CustomDebug)]
+pub struct Meters(#[debug = "{}m"] f64);
+
+#[derive(CustomDebug)]
+pub struct Pair<A, B>(A, B);
+
+#[derive(CustomDebug)]
+pub struct Unit;
+
+#[derive(CustomDebug)]
+pub enum Never {}
+
+fn main() {
+    assert_eq!(format!("{:?}", Error::<()>::NotFound), "NotFound");
+    assert_eq!(format!("{:?}", Error::Io(2, "open")), r#"Io(errno 2, "open")"#);
+    assert_eq!(
+        format!("{:?}", Error::<()>::Parse { line: 3, byte: 10 }),
+        "Parse { line: 3, byte: 0x0a }",
+    );
+    assert_eq!(format!("{:?}", Meters(1.5)), "Meters(1.5m)");
+    assert_eq!(format!("{:?}", Pair('a', "b")), r#"Pair('a', "b")"#);
+    assert_eq!(format!("{:?}", Unit), "Unit");
+    assert_eq!(format!("{:#?}", Meters(2.0)), "Meters(\n    2m,\n)");
+}
@@ -0,0 +1,38 @@
+// #[debug(skip)] leaves a field out of the output altogether, and the output
+// then ends in `..` through finish_non_exhaustive to show that something was
+// left out. #[debug(redact)] keeps the field but prints "***" in place of its
+// value. Neither puts any bound on the field's type.
+
+use derive_debug::CustomDebug;
+
+pub struct Connection;
+
+#[derive(CustomDebug)]
+pub struct Credentials<C> {
+    user: String,
+    #[debug(redact)]
+    password: String,
+    #[debug(skip)]
+    connection: C,
+}
+
+#[derive(CustomDebug)]
+pub enum Auth {
+    Token(#[debug(redact)] String),
+    Key(u32, #[debug(skip)] Vec<u8>),
+}
+
+fn main() {
+    let credentials = Credentials {
+        user: "admin".to_owned(),
+        password: "hunter2".to_owned(),
+        connection: Connection,
+    };
+    let debug = format!("{:?}", credentials);
+    assert_eq!(debug, r#"Credentials { user: "admin", password: "***", .. }"#);
+
+    let token = Auth::Token("secret".to_owned());
+    assert_eq!(format!("{:?}", token), r#"Token("***")"#);
+    let key = Auth::Key(7, vec![1, 2, 3]);
+    assert_eq!(format!("{:?}", key), "Key(7, ..)");
+}
@@ -0,0 +1,31 @@
+// A field that is skipped or redacted has no value printed, so it can neither
+// be both nor take a format string or a bound.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Both {
+    #[debug(redact, skip)]
+    value: String,
+}
+
+#[derive(CustomDebug)]
+pub struct Format {
+    #[debug = "{:?}"]
+    #[debug(redact)]
+    value: String,
+}
+
+#[derive(CustomDebug)]
+pub struct Bound<T> {
+    #[debug(skip, bound = "T: Clone")]
+    value: T,
+}
+
+#[derive(CustomDebug)]
+pub struct Unknown {
+    #[debug(hide)]
+    value: String,
+}
+
+fn main() {}